@@ -1,16 +1,18 @@
-use anyhow::{Context, Result};
+use anyhow::{Result, anyhow};
 use bon::Builder;
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
 use chrono::{DateTime, Utc};
 use clap::ValueEnum;
 use core::fmt;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
 
 use crate::{
     PlyConfig,
-    data::timestamp_filename,
+    data::{normalize_filename_attr, timestamp_filename},
     document::{Document, Filename, PreDocument},
     job::Job,
+    listing::{self, LISTINGS_DIR},
 };
 
 #[derive(Builder, Serialize, Deserialize, Clone)]
@@ -22,7 +24,15 @@ pub struct Application {
 
     pub cycle: Option<String>,
 
+    /// The listing snapshot taken when applying, relative to the data directory
+    pub listing_snapshot: Option<PathBuf>,
+
     pub stages: Vec<Stage>,
+
+    /// Top-level keys added by hand, e.g. which resume version was sent
+    #[serde(flatten)]
+    #[builder(default)]
+    pub extra: toml::Table,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize, Debug)]
@@ -43,11 +53,31 @@ impl StageType {
     pub fn is_terminal(&self) -> bool {
         matches!(self, Self::Rejected | Self::Accepted)
     }
+
+    /// The stage as it reads in a heading, e.g. "Team Matching".
+    pub fn title(&self) -> String {
+        let name = format!("{self:?}");
+        let mut title = String::new();
+
+        for c in name.chars() {
+            if c.is_uppercase() && !title.is_empty() {
+                title.push(' ');
+            }
+            title.push(c);
+        }
+
+        title
+    }
 }
 
+/// The stage as the CLI takes it, e.g. "team-matching", so that exports can be imported again.
 impl fmt::Display for StageType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", format!("{:?}", self).to_lowercase())
+        let value = self
+            .to_possible_value()
+            .expect("stage types should all be possible values");
+
+        write!(f, "{}", value.get_name())
     }
 }
 
@@ -57,6 +87,28 @@ pub struct Stage {
     pub deadline: Option<DateTime<Utc>>,
     pub name: Option<String>,
     pub stage_type: StageType,
+
+    /// Fields added by hand that ply doesn't know about, e.g. an interviewer
+    #[serde(flatten)]
+    pub extra: toml::Table,
+}
+
+/// A stage along with how long the application spent in it.
+#[derive(Serialize, Clone)]
+pub struct TimelineEntry {
+    #[serde(flatten)]
+    pub stage: Stage,
+
+    /// When the next stage started, if there is one
+    pub ended_at: Option<DateTime<Utc>>,
+
+    /// Days spent in the stage so far, if it isn't terminal
+    pub duration_days: Option<i64>,
+
+    pub is_current: bool,
+
+    /// Whether the stage is current and its deadline has passed
+    pub is_overdue: bool,
 }
 
 pub fn new(job: Job, cycle: Option<String>) -> Application {
@@ -70,6 +122,7 @@ pub fn new(job: Job, cycle: Option<String>) -> Application {
             deadline: None,
             stage_type: StageType::Applied,
             name: None,
+            extra: toml::Table::new(),
         }])
         .maybe_cycle(cycle)
         .build()
@@ -80,13 +133,50 @@ impl Application {
         Document {
             record: self.to_owned(),
             content: None,
+            frontmatter: None,
+            crlf: false,
+            bom: false,
+            path: None,
         }
     }
 
-    pub fn write_new_document(&self, config: &PlyConfig) -> Result<File> {
-        self.new_document()
-            .write_new(&config.data_dir)
-            .context("failed to write application")
+    /// The directory this application is filed under according to the configured layout, within
+    /// the archive if `archived`.
+    pub fn directory(&self, config: &PlyConfig, archived: bool) -> Result<PathBuf> {
+        let mut dir = config.data_dir.to_owned();
+        if archived {
+            dir.push(&config.archive_dir);
+        }
+
+        let Some(layout) = &config.layout else {
+            return Ok(dir);
+        };
+
+        let stage = self
+            .current_stage()
+            .map(|stage| stage.stage_type.to_string());
+        let year = self.applied_at.format("%Y").to_string();
+
+        for component in layout.split('/') {
+            let component = component
+                .replace("{cycle}", self.cycle.as_deref().unwrap_or_default())
+                .replace("{company}", &self.job.company)
+                .replace("{stage}", stage.as_deref().unwrap_or_default())
+                .replace("{year}", &year);
+
+            if component.contains(['{', '}']) {
+                return Err(anyhow!(
+                    "unknown placeholder in layout '{layout}', expected {{cycle}}, {{company}}, {{stage}} or {{year}}"
+                ));
+            }
+
+            let component = normalize_filename_attr(&component);
+            if !component.is_empty() {
+                dir.push(component);
+            }
+        }
+
+        Ok(dir)
     }
 
     pub fn pretty_print(&self) -> String {
@@ -97,12 +187,60 @@ impl Application {
         }
     }
 
+    /// The path to this application's listing snapshot, falling back to the latest snapshot of its
+    /// listing for applications created before snapshots were recorded
+    pub fn listing_snapshot_path(&self, data_dir: &Path) -> Result<Option<PathBuf>> {
+        if let Some(path) = &self.listing_snapshot {
+            return Ok(Some(data_dir.join(path)));
+        }
+
+        let Some(id) = self.job.id() else {
+            return Ok(None);
+        };
+
+        Ok(listing::snapshots(&data_dir.join(LISTINGS_DIR), &id)?
+            .pop()
+            .map(|snapshot| snapshot.path))
+    }
+
     pub fn current_stage(&self) -> Option<Stage> {
         let mut stages = self.stages.clone();
-        stages.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        stages.sort_by_key(|stage| stage.start_time);
         stages.last().cloned()
     }
 
+    /// The application's stages in order, with how long was spent in each as of the given time.
+    pub fn timeline(&self, now: DateTime<Utc>) -> Vec<TimelineEntry> {
+        let stages = self.pre_document().stages;
+
+        stages
+            .iter()
+            .enumerate()
+            .map(|(i, stage)| {
+                let ended_at = stages.get(i + 1).map(|next| next.start_time);
+                let is_current = ended_at.is_none();
+                let duration_days = (!(is_current && stage.stage_type.is_terminal()))
+                    .then(|| (ended_at.unwrap_or(now) - stage.start_time).num_days());
+
+                TimelineEntry {
+                    stage: stage.to_owned(),
+                    ended_at,
+                    duration_days,
+                    is_current,
+                    is_overdue: is_current
+                        && !stage.stage_type.is_terminal()
+                        && stage.deadline.is_some_and(|deadline| deadline < now),
+                }
+            })
+            .collect()
+    }
+
+    /// When the application last moved stages, or when it was applied to if it never has
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        self.current_stage()
+            .map_or(self.applied_at, |stage| stage.start_time)
+    }
+
     pub fn is_active(&self) -> bool {
         match self.current_stage() {
             Some(stage) => !stage.stage_type.is_terminal(),
@@ -0,0 +1,210 @@
+use std::fs::{self, File};
+
+use anyhow::{Context, Error, Result};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{
+    config::PlyConfig,
+    data::{ensure_directory, write_atomic},
+};
+
+const CACHE_DIR: &str = ".cache/http";
+const TRACKING_PARAM_PREFIXES: [&str; 3] = ["utm_", "gclid", "fbclid"];
+
+/// How fetches should make use of cached responses.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Revalidate cached responses with the server using their `ETag`/`Last-Modified` headers
+    #[default]
+    Revalidate,
+
+    /// Only serve cached responses, never touching the network
+    Offline,
+
+    /// Ignore cached responses and always refetch, overwriting the cache
+    Refresh,
+}
+
+/// An on-disk cache of raw fetched responses, keyed by canonical URL.
+#[derive(Clone)]
+pub struct HttpCache {
+    dir: PathBuf,
+    pub mode: CacheMode,
+}
+
+/// Every cache entry, split into the responses that could be read and the entries that couldn't.
+pub struct Entries {
+    pub entries: Vec<(PathBuf, CachedResponse)>,
+    pub failures: Vec<(PathBuf, Error)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedResponse {
+    pub url: Url,
+    pub fetched_at: DateTime<Utc>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+/// Strips fragments and tracking parameters and sorts the query so that trivially different
+/// links to the same listing share a cache entry.
+pub fn canonical_url(url: &Url) -> Url {
+    let mut canonical = url.to_owned();
+    canonical.set_fragment(None);
+
+    let mut pairs = url
+        .query_pairs()
+        .filter(|(key, _)| {
+            !TRACKING_PARAM_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix))
+        })
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    pairs.sort();
+
+    if pairs.is_empty() {
+        canonical.set_query(None);
+    } else {
+        canonical.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    canonical
+}
+
+impl HttpCache {
+    pub fn new(config: &PlyConfig, mode: CacheMode) -> Self {
+        HttpCache {
+            dir: config.data_dir.join(CACHE_DIR),
+            mode,
+        }
+    }
+
+    fn path(&self, url: &Url) -> PathBuf {
+        let hash = hex::encode(Sha256::digest(canonical_url(url).as_str()));
+        self.dir.join(format!("{hash}.json"))
+    }
+
+    fn read_entry(path: &Path) -> Result<CachedResponse> {
+        let file = File::open(path).context(format!("failed to open cache entry at {path}"))?;
+        serde_json::from_reader(file).context(format!("failed to parse cache entry at {path}"))
+    }
+
+    pub fn get(&self, url: &Url) -> Result<Option<CachedResponse>> {
+        let path = self.path(url);
+
+        if !path.try_exists().context(format!(
+            "failed to determine if cache entry exists at {path}"
+        ))? {
+            return Ok(None);
+        }
+
+        // A corrupt entry is only a cache miss, it'll be overwritten by the next fetch
+        match Self::read_entry(&path) {
+            Ok(response) => Ok(Some(response)),
+            Err(e) => {
+                eprintln!("warning: ignoring cached response for {url}: {e:#}");
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn put(&self, response: &CachedResponse) -> Result<()> {
+        ensure_directory(&self.dir)?;
+
+        let path = self.path(&response.url);
+        let content = serde_json::to_vec(response).context(format!(
+            "failed to serialize cache entry for {}",
+            response.url
+        ))?;
+
+        write_atomic(&path, &content, false)
+            .context(format!("failed to write cache entry at {path}"))
+    }
+
+    /// All cached responses along with their paths, oldest first, and the entries that couldn't be
+    /// read.
+    pub fn entries(&self) -> Result<Entries> {
+        let mut entries = Entries {
+            entries: vec![],
+            failures: vec![],
+        };
+
+        if !self.dir.is_dir() {
+            return Ok(entries);
+        }
+
+        for entry in self
+            .dir
+            .read_dir_utf8()
+            .context(format!("failed to read cache directory {}", self.dir))?
+        {
+            let entry = entry.context(format!("failed to read entry in {}", self.dir))?;
+            let path = entry.path();
+
+            if path.extension() == Some("json") {
+                match Self::read_entry(path) {
+                    Ok(response) => entries.entries.push((path.to_owned(), response)),
+                    Err(e) => entries.failures.push((path.to_owned(), e)),
+                }
+            }
+        }
+
+        entries
+            .entries
+            .sort_by_key(|(_, response)| response.fetched_at);
+        entries.failures.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(entries)
+    }
+
+    pub fn remove(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path).context(format!("failed to remove cache entry at {path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_entries_are_skipped() {
+        let dir = PathBuf::try_from(std::env::temp_dir())
+            .unwrap()
+            .join(format!("ply-test-{:08x}", rand::random::<u32>()));
+        let cache = HttpCache {
+            dir: dir.to_owned(),
+            mode: CacheMode::default(),
+        };
+
+        let url = Url::parse("https://jobs.ashbyhq.com/acme/1").unwrap();
+        cache
+            .put(&CachedResponse {
+                url: url.to_owned(),
+                fetched_at: Utc::now(),
+                etag: None,
+                last_modified: None,
+                body: "<html></html>".to_owned(),
+            })
+            .unwrap();
+        let corrupt_url = Url::parse("https://jobs.ashbyhq.com/acme/2").unwrap();
+        fs::write(cache.path(&corrupt_url), "{ truncated").unwrap();
+
+        let entries = cache.entries();
+        let corrupt = cache.get(&corrupt_url);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let entries = entries.unwrap();
+        assert_eq!(entries.entries.len(), 1);
+        assert_eq!(entries.entries[0].1.url, url);
+        assert_eq!(entries.failures.len(), 1);
+        assert_eq!(entries.failures[0].0, cache.path(&corrupt_url));
+        assert!(corrupt.unwrap().is_none());
+    }
+}
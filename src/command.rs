@@ -3,18 +3,33 @@ use clap::{Parser, Subcommand};
 
 use crate::{
     command::{
-        config::Config, cycles::Cycles, data_directory::DataDirectory, generate::Generate,
-        list::List, no::No, to::To, yes::Yes,
+        archive::Archive, cache::Cache, config::Config, cycles::Cycles,
+        data_directory::DataDirectory, delete::Delete, doctor::Doctor, edit::Edit, export::Export,
+        gc::Gc, generate::Generate, import::Import, list::List, listing::Listing, no::No,
+        normalize::Normalize, note::Note, reparse::Reparse, show::Show, to::To, yes::Yes,
     },
     config::PlyConfig,
 };
 
+mod archive;
+mod cache;
 mod config;
 mod cycles;
 mod data_directory;
+mod delete;
+mod doctor;
+mod edit;
+mod export;
+mod gc;
 mod generate;
+mod import;
 mod list;
+mod listing;
 mod no;
+mod normalize;
+mod note;
+mod reparse;
+mod show;
 mod to;
 mod yes;
 
@@ -31,6 +46,12 @@ pub struct Ply {
 
 #[derive(Subcommand)]
 pub enum Command {
+    /// Move closed applications into the archive, out of the working set
+    Archive(Archive),
+
+    /// Inspect and prune cached HTTP responses
+    Cache(Cache),
+
     /// Fetch the configured data directory
     DataDirectory(DataDirectory),
 
@@ -40,15 +61,48 @@ pub enum Command {
     /// Fetch all cycles with applications
     Cycles(Cycles),
 
+    /// Delete an application
+    Delete(Delete),
+
+    /// Report every document in the data directory that can't be read, and why
+    Doctor(Doctor),
+
+    /// Open an application in $VISUAL or $EDITOR, validating it on save
+    Edit(Edit),
+
+    /// Export applications for analysis elsewhere
+    Export(Export),
+
+    /// Find orphaned listing snapshots and applications missing their snapshot
+    Gc(Gc),
+
     /// Generate completions for this CLI
     Generate(Generate),
 
+    /// Import applications from a CSV or JSON file, e.g. one from `ply export`, or from Notion
+    Import(Import),
+
     /// List data about applications
     List(List),
 
+    /// Inspect the snapshots of applications' job listings
+    Listing(Listing),
+
     /// Mark an application as rejected
     No(No),
 
+    /// Add a timestamped note to an application under its current stage
+    Note(Note),
+
+    /// Rename applications whose filenames no longer match their company, title and team
+    Normalize(Normalize),
+
+    /// Rebuild applications' job details from their raw HTML listing snapshots
+    Reparse(Reparse),
+
+    /// Show an application or its job listing snapshot
+    Show(Show),
+
     /// Create an application to a job listing
     To(To),
 
@@ -63,12 +117,25 @@ pub fn parse() -> Ply {
 impl Run for Ply {
     fn run(&self, config: &PlyConfig) -> Result<()> {
         match &self.command {
+            Command::Archive(cmd) => cmd.run(config),
+            Command::Cache(cmd) => cmd.run(config),
             Command::Config(cmd) => cmd.run(config),
             Command::Cycles(cmd) => cmd.run(config),
             Command::DataDirectory(cmd) => cmd.run(config),
+            Command::Delete(cmd) => cmd.run(config),
+            Command::Doctor(cmd) => cmd.run(config),
+            Command::Edit(cmd) => cmd.run(config),
+            Command::Export(cmd) => cmd.run(config),
+            Command::Gc(cmd) => cmd.run(config),
             Command::Generate(cmd) => cmd.run(config),
+            Command::Import(cmd) => cmd.run(config),
             Command::List(cmd) => cmd.run(config),
+            Command::Listing(cmd) => cmd.run(config),
             Command::No(cmd) => cmd.run(config),
+            Command::Normalize(cmd) => cmd.run(config),
+            Command::Note(cmd) => cmd.run(config),
+            Command::Reparse(cmd) => cmd.run(config),
+            Command::Show(cmd) => cmd.run(config),
             Command::To(cmd) => cmd.run(config),
             Command::Yes(cmd) => cmd.run(config),
         }
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Archive {
+    /// The paths to the application documents to archive, or their IDs
+    #[arg(required_unless_present("all"))]
+    pub applications: Vec<String>,
+
+    /// Archive every closed application in the data directory
+    #[arg(long, conflicts_with("applications"))]
+    pub all: bool,
+
+    /// Only print the moves that would be made
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl Run for Archive {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let repository = Repository::new(config);
+
+        for mut document in repository.find_all(&self.applications, self.all)? {
+            let Some(path) = document.path.to_owned() else {
+                continue;
+            };
+
+            if repository.is_archived(&path) {
+                continue;
+            }
+
+            if document.record.is_active() {
+                if !self.all {
+                    println!(
+                        "application for '{}' is still active, skipping",
+                        document.record.pretty_print()
+                    );
+                }
+
+                continue;
+            }
+
+            if self.dry_run {
+                let dir = document.record.directory(config, true)?;
+                println!(
+                    "{path} -> {}",
+                    dir.join(path.file_name().unwrap_or_default())
+                );
+            } else if let Some(moved) = repository.archive(&mut document)? {
+                println!("{path} -> {moved}");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    PlyConfig,
+    cache::{CacheMode, HttpCache},
+    command::Run,
+};
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct Cache {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// List cached responses, oldest first
+    List(List),
+
+    /// Remove cached responses
+    Prune(Prune),
+}
+
+#[derive(Args)]
+pub struct List {}
+
+#[derive(Args)]
+pub struct Prune {
+    /// Only remove responses fetched more than this many days ago
+    #[arg(long)]
+    older_than: Option<u32>,
+}
+
+impl Run for List {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let cache = HttpCache::new(config, CacheMode::default());
+
+        let entries = cache.entries()?;
+
+        for (_, response) in entries.entries {
+            println!(
+                "{}\t{}\t{}",
+                response.fetched_at.format("%Y-%m-%d %H:%M:%S"),
+                response.body.len(),
+                response.url
+            );
+        }
+
+        for (_, e) in entries.failures {
+            eprintln!("warning: {e:#}");
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for Prune {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let cache = HttpCache::new(config, CacheMode::default());
+        let cutoff = self
+            .older_than
+            .map(|days| Utc::now() - TimeDelta::days(days.into()));
+
+        let entries = cache.entries()?;
+
+        let mut pruned = 0;
+        for (path, response) in entries.entries {
+            if cutoff.is_some_and(|cutoff| response.fetched_at > cutoff) {
+                continue;
+            }
+
+            cache.remove(&path)?;
+            pruned += 1;
+        }
+
+        // Entries that can't be read are never served, so they're pruned however old they are
+        for (path, _) in entries.failures {
+            cache.remove(&path)?;
+            pruned += 1;
+        }
+
+        println!("pruned {pruned} cached response(s)");
+
+        Ok(())
+    }
+}
+
+impl Run for Cache {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        match &self.command {
+            CacheCommand::List(cmd) => cmd.run(config),
+            CacheCommand::Prune(cmd) => cmd.run(config),
+        }
+    }
+}
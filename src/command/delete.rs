@@ -0,0 +1,56 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Delete {
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
+    pub application: String,
+
+    /// Delete without asking for confirmation
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+fn confirm_delete(application: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    eprint!("delete the application for '{application}'? [y/N] ");
+    io::stderr().flush().context("failed to flush STDERR")?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("failed to read answer from STDIN")?;
+
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+impl Run for Delete {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let repository = Repository::new(config);
+        let document = repository.find(&self.application)?;
+        let application = document.record.pretty_print();
+
+        if !self.yes && !confirm_delete(&application)? {
+            return Err(anyhow!(
+                "application for '{application}' was not deleted, pass --yes to delete it without confirmation"
+            ));
+        }
+
+        repository.delete(document)?;
+        println!("application for '{application}' deleted");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    document::FrontmatterError,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Doctor {}
+
+impl Run for Doctor {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let all = Repository::new(config).all()?;
+
+        for (path, e) in &all.failures {
+            match e.downcast_ref::<FrontmatterError>() {
+                Some(e) => println!("{e}"),
+                None => println!("{path}: {e:#}"),
+            }
+        }
+
+        let total = all.documents.len() + all.failures.len();
+
+        if all.failures.is_empty() {
+            println!("all {total} document(s) are readable");
+            return Ok(());
+        }
+
+        Err(anyhow!(
+            "{} of {total} document(s) could not be read",
+            all.failures.len()
+        ))
+    }
+}
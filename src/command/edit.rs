@@ -0,0 +1,127 @@
+use std::{
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    application::Application,
+    command::Run,
+    document,
+    editor::{self, Scratch},
+    repository::{Repository, Store},
+};
+
+/// Prefixes the parse error comments shown in the frontmatter when an edit fails validation.
+const ERROR_PREFIX: &str = "# ply error: ";
+
+#[derive(Args)]
+pub struct Edit {
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
+    pub application: String,
+}
+
+fn strip_errors(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with(ERROR_PREFIX))
+        .collect()
+}
+
+/// Show the error as TOML comments at the top of the frontmatter so it's visible in the editor.
+fn with_errors(content: &str, error: &anyhow::Error) -> String {
+    let errors = format!("{error:#}")
+        .lines()
+        .map(|line| format!("{ERROR_PREFIX}{line}\n"))
+        .collect::<String>();
+
+    match content.strip_prefix("---\n") {
+        Some(rest) => format!("---\n{errors}{rest}"),
+        None => format!("{errors}{content}"),
+    }
+}
+
+fn confirm_reopen() -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    eprint!("reopen the editor to fix it? [Y/n] ");
+    io::stderr().flush().context("failed to flush STDERR")?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("failed to read answer from STDIN")?;
+
+    Ok(!answer.trim().eq_ignore_ascii_case("n"))
+}
+
+impl Run for Edit {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let repository = Repository::new(config);
+        let path = repository
+            .find(&self.application)?
+            .path
+            .context("application has no path")?;
+        let original =
+            fs::read_to_string(&path).context(format!("failed to read document at {path}"))?;
+
+        // Edits are made to a copy so that the document is never left invalid
+        let copy = Scratch::new(&path, "edit", &original)?;
+        let scratch = &copy.path;
+
+        let result = loop {
+            editor::open(scratch)?;
+
+            let content = fs::read_to_string(scratch)
+                .context(format!("failed to read scratch copy at {scratch}"))?;
+            let content = strip_errors(&content);
+
+            if content == original {
+                break Ok(None);
+            }
+
+            fs::write(scratch, &content)
+                .context(format!("failed to write scratch copy at {scratch}"))?;
+
+            match document::read::<Application>(scratch) {
+                Ok(document) => break Ok(Some(document)),
+                Err(e) => {
+                    eprintln!("invalid application: {e:#}");
+
+                    if !confirm_reopen()? {
+                        break Err(e.context("edit aborted, the document was left unchanged"));
+                    }
+
+                    fs::write(scratch, with_errors(&content, &e))
+                        .context(format!("failed to write scratch copy at {scratch}"))?;
+                }
+            }
+        };
+        drop(copy);
+
+        match result? {
+            Some(mut document) => {
+                // The edited copy takes the original's place
+                document.path = Some(path.to_owned());
+                repository.update(&mut document)?;
+                repository.rename(&mut document)?;
+
+                let renamed = document.path.to_owned().unwrap_or_default();
+                if renamed == path {
+                    println!("updated application at {path}");
+                } else {
+                    println!("updated application and renamed it to {renamed}");
+                }
+            }
+            None => println!("no changes made to {path}"),
+        }
+
+        Ok(())
+    }
+}
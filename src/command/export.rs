@@ -0,0 +1,148 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+    thread::sleep,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf as PathBuf;
+use chrono::Utc;
+use clap::{Args, Subcommand};
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    export::{self, ApplicationRow, Format, StageRow},
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Export {
+    #[command(subcommand)]
+    command: Option<ExportCommand>,
+
+    /// The format to export applications in
+    #[arg(value_enum, long, short, default_value = "csv")]
+    format: Format,
+
+    /// Export a row per stage of each application rather than a row per application
+    #[arg(long)]
+    stages: bool,
+
+    /// The file to export to instead of STDOUT
+    #[arg(long, short)]
+    output: Option<PathBuf>,
+
+    /// Fail on unreadable documents instead of skipping them
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ExportCommand {
+    /// Export every application into a SQLite database, replacing it if it exists
+    Sqlite(Sqlite),
+}
+
+#[derive(Args)]
+pub struct Sqlite {
+    /// The path to the database
+    pub path: PathBuf,
+
+    /// Keep running, exporting again whenever an application changes
+    #[arg(long)]
+    pub watch: bool,
+
+    /// How often to check for changes while watching, in seconds
+    #[arg(long, default_value_t = 2, requires("watch"))]
+    pub interval: u64,
+}
+
+impl Sqlite {
+    fn export(&self, config: &PlyConfig) -> Result<()> {
+        let documents = Repository::new(config).all()?.into_documents(false)?;
+        export::sqlite(&self.path, &documents, Utc::now())?;
+
+        println!(
+            "exported {} application(s) to {}",
+            documents.len(),
+            self.path
+        );
+
+        Ok(())
+    }
+}
+
+impl Run for Sqlite {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let repository = Repository::new(config);
+        let mut exported = repository.modification_times()?;
+        self.export(config)?;
+
+        if !self.watch {
+            return Ok(());
+        }
+
+        loop {
+            sleep(Duration::from_secs(self.interval));
+
+            let current = repository.modification_times()?;
+            if current == exported {
+                continue;
+            }
+
+            if let Err(e) = self.export(config) {
+                eprintln!("failed to export applications: {e:#}");
+                continue;
+            }
+
+            exported = current;
+        }
+    }
+}
+
+impl Export {
+    fn export(&self, config: &PlyConfig, writer: impl Write) -> Result<()> {
+        let records = Repository::new(config)
+            .records()?
+            .into_records(self.strict)?;
+
+        if self.stages {
+            let now = Utc::now();
+            let rows = records
+                .iter()
+                .flat_map(|(path, application)| StageRow::all(path, application, now))
+                .collect::<Vec<_>>();
+
+            return export::rows(writer, &rows, self.format);
+        }
+
+        let rows = records
+            .iter()
+            .map(|(path, application)| ApplicationRow::new(path, application))
+            .collect::<Vec<_>>();
+
+        export::rows(writer, &rows, self.format)
+    }
+}
+
+impl Run for Export {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        if let Some(command) = &self.command {
+            return match command {
+                ExportCommand::Sqlite(cmd) => cmd.run(config),
+            };
+        }
+
+        match &self.output {
+            Some(path) => {
+                let file =
+                    File::create(path).context(format!("failed to create export file {path}"))?;
+                self.export(config, file)
+            }
+            None => self.export(config, io::stdout().lock()),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{collections::HashSet, fs};
+
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    listing::LISTINGS_DIR,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Gc {
+    /// Delete orphaned listing snapshots instead of only reporting them
+    #[arg(long)]
+    pub delete: bool,
+}
+
+impl Run for Gc {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let records = Repository::new(config).records()?;
+
+        // The snapshots of documents that can't be read would look orphaned, so nothing can be
+        // deleted unless every document was read
+        let unreadable = records.failures.len();
+        if self.delete && unreadable > 0 {
+            return Err(anyhow!(
+                "refusing to delete listing snapshots while {unreadable} document(s) can't be read, run `ply doctor` for details"
+            ));
+        }
+
+        let records = records.into_records(false)?;
+
+        let mut referenced: HashSet<String> = HashSet::new();
+
+        for (path, record) in records {
+            if let Some(id) = record.job.id() {
+                referenced.insert(id);
+            }
+
+            match record.listing_snapshot_path(&config.data_dir)? {
+                Some(snapshot) if !snapshot.is_file() => println!(
+                    "application for '{}' is missing its listing snapshot {} ({})",
+                    record.pretty_print(),
+                    snapshot,
+                    path
+                ),
+                None if record.job.listing_url.is_some() => println!(
+                    "application for '{}' has no listing snapshot ({})",
+                    record.pretty_print(),
+                    path
+                ),
+                _ => {}
+            }
+        }
+
+        let content_dir = config.data_dir.join(LISTINGS_DIR);
+        if !content_dir.is_dir() {
+            return Ok(());
+        }
+
+        let mut orphans = content_dir
+            .read_dir_utf8()
+            .context(format!("failed to read content directory {content_dir}"))?
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .split('.')
+                    .next()
+                    .is_some_and(|id| !referenced.contains(id))
+            })
+            .map(|entry| entry.path().to_owned())
+            .collect::<Vec<_>>();
+        orphans.sort();
+
+        if unreadable > 0 && !orphans.is_empty() {
+            eprintln!(
+                "warning: some of these snapshots may belong to the {unreadable} document(s) that can't be read"
+            );
+        }
+
+        for orphan in &orphans {
+            if self.delete {
+                fs::remove_file(orphan).context(format!(
+                    "failed to delete orphaned listing snapshot {orphan}"
+                ))?;
+                println!("deleted orphaned listing snapshot {orphan}");
+            } else {
+                println!("orphaned listing snapshot {orphan}");
+            }
+        }
+
+        Ok(())
+    }
+}
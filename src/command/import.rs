@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf as PathBuf;
+use clap::{Args, Subcommand};
+
+use crate::{
+    PlyConfig,
+    application::{Application, StageType},
+    command::Run,
+    document::Document,
+    export::Format,
+    import::{self, Field, Mapping, is_duplicate, notion, parse_mapping, parse_status},
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct Import {
+    #[command(subcommand)]
+    command: Option<ImportCommand>,
+
+    /// The CSV, JSON or JSON lines file to import applications from, e.g. one exported from
+    /// `ply export` or Airtable
+    #[arg(required = true)]
+    pub path: Option<PathBuf>,
+
+    /// The format of the file, inferred from its extension if not given
+    #[arg(value_enum, long, short)]
+    pub format: Option<Format>,
+
+    #[command(flatten)]
+    pub options: ImportOptions,
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Import applications from a Notion database exported as Markdown & CSV
+    Notion(Notion),
+}
+
+#[derive(Args)]
+pub struct Notion {
+    /// The exported zip, or the directory it was extracted to
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub options: ImportOptions,
+}
+
+#[derive(Args)]
+pub struct ImportOptions {
+    /// Map a column or property onto an application field, given as COLUMN=FIELD. Those named
+    /// after a field or a common alias of it, e.g. "Role" for title, are mapped automatically
+    #[arg(long = "map", value_name = "COLUMN=FIELD", value_parser = parse_mapping)]
+    pub mappings: Vec<(String, Field)>,
+
+    /// Map a status onto a stage, given as STATUS=STAGE, e.g. "Phone Screen=screen". Statuses
+    /// named after a stage are mapped automatically
+    #[arg(long = "status", value_name = "STATUS=STAGE", value_parser = parse_status)]
+    pub statuses: Vec<(String, StageType)>,
+
+    /// The cycle for imported applications without one
+    #[arg(long, short)]
+    pub cycle: Option<String>,
+
+    /// Print what would be imported without importing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl ImportOptions {
+    fn mapping(&self) -> Mapping {
+        Mapping {
+            columns: self.mappings.to_owned(),
+            statuses: self.statuses.to_owned(),
+        }
+    }
+
+    fn cycle<'a>(&'a self, config: &'a PlyConfig) -> Option<&'a str> {
+        self.cycle.as_deref().or(config.default_cycle.as_deref())
+    }
+}
+
+/// Create a document for each imported application, skipping those that duplicate an existing
+/// application or one imported before it. Every application is built before any are created so
+/// that a bad row doesn't leave an import half-done.
+pub fn create_all(
+    config: &PlyConfig,
+    documents: Vec<Document<Application>>,
+    dry_run: bool,
+) -> Result<()> {
+    let repository = Repository::new(config);
+    let mut existing = repository.records()?.into_records(false)?;
+    let mut imported = 0;
+
+    for document in documents {
+        let application = &document.record;
+
+        if let Some((path, _)) = existing
+            .iter()
+            .find(|(_, existing)| is_duplicate(existing, application))
+        {
+            println!(
+                "skipping '{}', it duplicates {}",
+                application.pretty_print(),
+                path.strip_prefix(&config.data_dir).unwrap_or(path)
+            );
+            continue;
+        }
+
+        let path = if dry_run {
+            println!("would import '{}'", application.pretty_print());
+            PathBuf::new()
+        } else {
+            let path = repository.create(&document)?;
+            println!(
+                "imported '{}' to {}",
+                application.pretty_print(),
+                path.strip_prefix(&config.data_dir).unwrap_or(&path)
+            );
+            path
+        };
+
+        imported += 1;
+        existing.push((path, document.record));
+    }
+
+    if dry_run {
+        println!("would import {imported} application(s)");
+    } else {
+        println!("imported {imported} application(s)");
+    }
+
+    Ok(())
+}
+
+impl Run for Notion {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let options = &self.options;
+        let documents = notion::documents(&self.path, &options.mapping(), options.cycle(config))?;
+
+        create_all(config, documents, options.dry_run)
+    }
+}
+
+impl Run for Import {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        if let Some(command) = &self.command {
+            return match command {
+                ImportCommand::Notion(cmd) => cmd.run(config),
+            };
+        }
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let options = &self.options;
+        let mapping = options.mapping();
+        let cycle = options.cycle(config);
+
+        let documents = import::read_rows(path, self.format)?
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                mapping
+                    .document(row, cycle)
+                    .context(format!("failed to import row {}", i + 1))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        create_all(config, documents, options.dry_run)
+    }
+}
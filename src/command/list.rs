@@ -1,10 +1,8 @@
-use camino::Utf8Path as Path;
-use camino::Utf8PathBuf as PathBuf;
-use std::{collections::BTreeSet, fs};
+use std::collections::BTreeSet;
 
-use crate::document::read;
-use crate::{PlyConfig, application::Application, command::Run, document::Document};
-use anyhow::{Context, Result};
+use crate::repository::{Repository, Store};
+use crate::{PlyConfig, command::Run};
+use anyhow::Result;
 use clap::{Args, Subcommand};
 
 #[derive(Args)]
@@ -31,39 +29,42 @@ pub struct Applications {
     /// Only list applications that are past the initial 'Applied' stage and not in a terminal state like accepted or rejected
     #[arg(short, long)]
     interviewing: bool,
+
+    /// Also list archived applications
+    #[arg(long)]
+    archived: bool,
+
+    /// Fail on unreadable documents instead of skipping them
+    #[arg(long)]
+    strict: bool,
 }
 
 #[derive(Args)]
-pub struct Companies {}
+pub struct Companies {
+    /// Fail on unreadable documents instead of skipping them
+    #[arg(long)]
+    strict: bool,
+}
 
 impl Run for Applications {
     fn run(&self, config: &PlyConfig) -> Result<()> {
-        let read_dir = fs::read_dir(&config.data_dir).context(format!(
-            "failed to read files in data directory {}",
-            &config.data_dir
-        ))?;
-
-        for entry in read_dir {
-            if let Ok(entry) = entry
-                && let Some(path) = Path::from_path(&entry.path())
-                && path.is_file()
-            {
-                let maybe_doc: Option<Document<Application>> = PathBuf::try_from(entry.path())
-                    .ok()
-                    .and_then(|path| read(&path).ok());
-
-                if let Some(doc) = maybe_doc {
-                    if self.active && !doc.record.is_active() {
-                        continue;
-                    }
-
-                    if self.interviewing && !doc.record.is_interviewing() {
-                        continue;
-                    }
-
-                    println!("{}", entry.path().to_string_lossy())
-                };
+        let repository = Repository::new(config);
+        let records = repository.records()?.into_records(self.strict)?;
+
+        for (path, record) in records {
+            if !self.archived && repository.is_archived(&path) {
+                continue;
+            }
+
+            if self.active && !record.is_active() {
+                continue;
+            }
+
+            if self.interviewing && !record.is_interviewing() {
+                continue;
             }
+
+            println!("{}", path)
         }
 
         Ok(())
@@ -72,25 +73,13 @@ impl Run for Applications {
 
 impl Run for Companies {
     fn run(&self, config: &PlyConfig) -> Result<()> {
-        let entries = fs::read_dir(&config.data_dir).context(format!(
-            "failed to read files in data directory {}",
-            &config.data_dir
-        ))?;
+        let records = Repository::new(config)
+            .records()?
+            .into_records(self.strict)?;
 
         let mut companies: BTreeSet<String> = BTreeSet::new();
-        for entry in entries {
-            if let Ok(entry) = entry
-                && let Some(path) = Path::from_path(&entry.path())
-                && path.is_file()
-            {
-                let maybe_doc: Option<Document<Application>> = PathBuf::try_from(entry.path())
-                    .ok()
-                    .and_then(|path| read(&path).ok());
-
-                if let Some(doc) = maybe_doc {
-                    companies.insert(doc.record.job.company);
-                };
-            }
+        for (_, record) in records {
+            companies.insert(record.job.company);
         }
 
         for company in companies {
@@ -0,0 +1,291 @@
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use clap::{Args, Subcommand};
+use similar::TextDiff;
+
+use crate::{
+    PlyConfig,
+    application::Application,
+    cache::{CacheMode, HttpCache},
+    command::Run,
+    fetch::{Fetch, FetchOptions, HttpStatusError, Source},
+    job::SalaryRange,
+    listing::{self, LISTINGS_DIR},
+    parse::Parser,
+    repository::{Repository, Store},
+    scrape::ScrapedContent,
+};
+use url::Url;
+
+#[derive(Args)]
+pub struct Listing {
+    #[command(subcommand)]
+    command: ListingCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ListingCommand {
+    /// Show what changed in an application's listing between snapshots
+    Diff(Diff),
+
+    /// Re-fetch the listings of active applications and report those that were taken down
+    Check(Check),
+}
+
+#[derive(Args)]
+pub struct Diff {
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
+    pub application: String,
+
+    /// The version to diff from, counting from 1 for the oldest, defaults to the second latest
+    #[arg(long)]
+    pub from: Option<usize>,
+
+    /// The version to diff to, counting from 1 for the oldest, defaults to the latest
+    #[arg(long)]
+    pub to: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct Check {}
+
+fn format_salary_range(salary_range: &Option<SalaryRange>) -> String {
+    salary_range
+        .as_ref()
+        .map_or("none".to_owned(), |salary_range| salary_range.to_string())
+}
+
+impl Run for Diff {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let document = Repository::new(config).find(&self.application)?;
+        let id = document
+            .record
+            .job
+            .id()
+            .ok_or(anyhow!("application has no listing URL"))?;
+
+        let snapshots = listing::snapshots(&config.data_dir.join(LISTINGS_DIR), &id)?;
+
+        if snapshots.len() < 2 {
+            println!(
+                "listing for '{}' has {} snapshot(s), nothing to diff",
+                document.record.pretty_print(),
+                snapshots.len()
+            );
+            return Ok(());
+        }
+
+        let to = self.to.unwrap_or(snapshots.len());
+        let from = self.from.unwrap_or(to.saturating_sub(1));
+
+        let version = |n: usize| {
+            n.checked_sub(1)
+                .and_then(|i| snapshots.get(i))
+                .ok_or(anyhow!(
+                    "no snapshot version {n}, expected 1 to {}",
+                    snapshots.len()
+                ))
+        };
+
+        let (from_snapshot, to_snapshot) = (version(from)?, version(to)?);
+        let (_, from_body) = from_snapshot.read()?;
+        let (_, to_body) = to_snapshot.read()?;
+
+        let from_salary_range = from_snapshot.salary_range().unwrap_or(None);
+        let to_salary_range = to_snapshot.salary_range().unwrap_or(None);
+
+        if from_salary_range != to_salary_range {
+            println!(
+                "salary range changed from {} to {}",
+                format_salary_range(&from_salary_range),
+                format_salary_range(&to_salary_range)
+            );
+        }
+
+        if from_body == to_body {
+            println!("description unchanged");
+        } else {
+            print!(
+                "{}",
+                TextDiff::from_lines(&from_body, &to_body)
+                    .unified_diff()
+                    .header(from_snapshot.path.as_str(), to_snapshot.path.as_str())
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// What re-fetching a listing found.
+enum Status {
+    Up,
+    Down(String),
+
+    /// The listing couldn't be checked, e.g. because there's no parser to tell whether it's open
+    Unknown(String),
+}
+
+impl Check {
+    /// The parser recorded by the latest snapshot, for listings whose parser can't be inferred
+    fn snapshotted_parser(config: &PlyConfig, application: &Application) -> Option<Parser> {
+        let id = application.job.id()?;
+        let latest = listing::snapshots(&config.data_dir.join(LISTINGS_DIR), &id)
+            .ok()?
+            .pop()?;
+
+        latest
+            .read()
+            .ok()?
+            .0
+            .map(|header| header.parser)
+            .filter(|parser| *parser != Parser::Unimplemented)
+    }
+
+    /// The URL to re-fetch along with the parser for it. That's the source listing if there's a
+    /// parser for it, and otherwise the listing itself, which is then what was snapshotted, so the
+    /// parser it was snapshotted with applies when none can be inferred.
+    fn target<'a>(
+        config: &PlyConfig,
+        application: &'a Application,
+    ) -> Option<(&'a Url, Option<Parser>)> {
+        let inferred =
+            |url: &Url| Parser::infer(url).filter(|parser| *parser != Parser::Unimplemented);
+        let job = &application.job;
+
+        if let Some((url, parser)) = job
+            .source_url
+            .as_ref()
+            .and_then(|url| inferred(url).map(|parser| (url, parser)))
+        {
+            return Some((url, Some(parser)));
+        }
+
+        let url = job.listing_url.as_ref()?;
+        let parser = inferred(url).or_else(|| Self::snapshotted_parser(config, application));
+
+        Some((url, parser))
+    }
+
+    /// Re-fetch the application's listing, snapshotting it as a new version if it's still up.
+    fn check(
+        &self,
+        config: &PlyConfig,
+        options: &FetchOptions,
+        application: &Application,
+    ) -> Result<Status> {
+        let Some((url, parser)) = Self::target(config, application) else {
+            return Ok(Status::Unknown("has no URL to check".to_owned()));
+        };
+
+        if !matches!(url.scheme(), "https" | "http") {
+            return Ok(Status::Unknown(format!(
+                "can't be checked, {url} isn't on the web"
+            )));
+        }
+
+        let content = match Source::new(url, options)?.fetch() {
+            Ok(content) => content,
+            Err(e) => {
+                return match e.downcast_ref::<HttpStatusError>() {
+                    Some(e) if matches!(e.status, 404 | 410) => Ok(Status::Down(format!(
+                        "was taken down, got status {}",
+                        e.status
+                    ))),
+                    _ => Err(e),
+                };
+            }
+        };
+
+        let Some(parser) = parser else {
+            return Ok(Status::Unknown(format!(
+                "can't be checked, there's no parser for {url} to tell whether it's still open"
+            )));
+        };
+
+        if !matches!(parser.parse_role(&content), Ok(Some(_))) {
+            return Ok(Status::Down(
+                "may be closed, it can no longer be parsed".to_owned(),
+            ));
+        }
+
+        ScrapedContent {
+            job: application.job.to_owned(),
+            content,
+            parser,
+            fetched_at: Utc::now(),
+        }
+        .snapshot(
+            &config.data_dir.join(LISTINGS_DIR),
+            config.compress_snapshots,
+        )?;
+
+        Ok(Status::Up)
+    }
+}
+
+impl Run for Check {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let records = Repository::new(config).records()?.into_records(false)?;
+
+        let options = FetchOptions {
+            http: config.http.to_owned(),
+            cache: Some(HttpCache::new(config, CacheMode::Refresh)),
+        };
+
+        let mut checked = 0;
+        let mut down = 0;
+        let mut unknown = 0;
+
+        for (path, application) in records {
+            if !application.is_active() || application.job.listing_url.is_none() {
+                continue;
+            }
+
+            checked += 1;
+
+            let status = match self.check(config, &options, &application) {
+                Ok(Status::Up) => continue,
+                Ok(Status::Down(status)) => {
+                    down += 1;
+                    status
+                }
+                Ok(Status::Unknown(status)) => {
+                    unknown += 1;
+                    status
+                }
+                Err(e) => {
+                    eprintln!(
+                        "failed to check listing for '{}': {e:#}",
+                        application.pretty_print()
+                    );
+                    continue;
+                }
+            };
+
+            println!(
+                "listing for '{}' {} ({})",
+                application.pretty_print(),
+                status,
+                path
+            );
+        }
+
+        print!("checked {checked} listing(s), {down} appear to be down");
+        if unknown > 0 {
+            print!(", {unknown} couldn't be checked");
+        }
+        println!();
+
+        Ok(())
+    }
+}
+
+impl Run for Listing {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        match &self.command {
+            ListingCommand::Diff(cmd) => cmd.run(config),
+            ListingCommand::Check(cmd) => cmd.run(config),
+        }
+    }
+}
@@ -1,23 +1,28 @@
 use crate::{
-    application::{Application, Stage, StageType},
+    application::{Stage, StageType},
     command::Run,
     config::PlyConfig,
-    document,
+    notes,
+    repository::{Repository, Store},
 };
 use anyhow::{Context, Result};
-use camino::Utf8Path as Path;
 use chrono::Utc;
 use clap::Args;
 
 #[derive(Args)]
 pub struct No {
-    /// The path to the application document
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
     pub path: String,
+
+    /// A note to add under the rejection, e.g. the reason given
+    #[arg(long)]
+    pub note: Option<String>,
 }
 
 impl Run for No {
     fn run(&self, config: &PlyConfig) -> Result<()> {
-        let mut document = document::read::<Application>(Path::new(&self.path))?;
+        let repository = Repository::new(config);
+        let mut document = repository.find(&self.path)?;
 
         if !document.record.is_active()
             && let Some(stage) = document.record.current_stage()
@@ -30,15 +35,23 @@ impl Run for No {
             return Ok(());
         }
 
-        document.record.stages.push(Stage {
-            start_time: Utc::now(),
+        let now = Utc::now();
+        let stage = Stage {
+            start_time: now,
             deadline: None,
             name: None,
             stage_type: StageType::Rejected,
-        });
+            extra: toml::Table::new(),
+        };
+        document.record.stages.push(stage.to_owned());
+        notes::add_stage_section(config, &mut document, &stage)?;
+
+        if let Some(note) = &self.note {
+            notes::add_note(&mut document, note, now);
+        }
 
-        document
-            .write(&config.data_dir)
+        let moved = repository
+            .update(&mut document)
             .context("failed to write new stage to document")?;
 
         println!(
@@ -46,6 +59,10 @@ impl Run for No {
             document.record.pretty_print()
         );
 
+        if let Some(moved) = moved {
+            println!("moved application to {moved}");
+        }
+
         Ok(())
     }
 }
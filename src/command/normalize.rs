@@ -0,0 +1,49 @@
+use anyhow::Result;
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Normalize {
+    /// The paths to the application documents to rename, or their IDs
+    #[arg(required_unless_present("all"))]
+    pub applications: Vec<String>,
+
+    /// Rename every application in the data directory
+    #[arg(long, conflicts_with("applications"))]
+    pub all: bool,
+
+    /// Only print the renames that would be made
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl Run for Normalize {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let repository = Repository::new(config);
+
+        for mut document in repository.find_all(&self.applications, self.all)? {
+            let (Some(path), Some(normalized)) =
+                (document.path.to_owned(), document.normalized_path())
+            else {
+                continue;
+            };
+
+            if path == normalized {
+                continue;
+            }
+
+            if !self.dry_run {
+                repository.rename(&mut document)?;
+            }
+
+            println!("{path} -> {normalized}");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    fs,
+    io::{self, IsTerminal},
+};
+
+use anyhow::{Context, Result, anyhow};
+use camino::Utf8Path as Path;
+use chrono::Utc;
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    command::Run,
+    editor::{self, Scratch},
+    notes,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Note {
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
+    pub application: String,
+
+    /// The note, read from STDIN or written in $VISUAL or $EDITOR if not given
+    pub note: Option<String>,
+}
+
+/// Write a note in the user's editor, starting from an empty file next to the document.
+fn write_note(document: &Path) -> Result<String> {
+    let scratch = Scratch::new(document, "note", "")?;
+
+    editor::open(&scratch.path)?;
+
+    fs::read_to_string(&scratch.path).context(format!("failed to read {}", scratch.path))
+}
+
+impl Run for Note {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let repository = Repository::new(config);
+        let mut document = repository.find(&self.application)?;
+
+        let note = match &self.note {
+            Some(note) => note.to_owned(),
+            None if !io::stdin().is_terminal() => {
+                io::read_to_string(io::stdin()).context("failed to read note from STDIN")?
+            }
+            None => write_note(document.path.as_ref().context("application has no path")?)?,
+        };
+
+        if note.trim().is_empty() {
+            return Err(anyhow!("note is empty, nothing was added"));
+        }
+
+        notes::add_note(&mut document, &note, Utc::now());
+
+        let moved = repository
+            .update(&mut document)
+            .context("failed to write note to document")?;
+
+        println!(
+            "note added to application for '{}'",
+            document.record.pretty_print()
+        );
+
+        if let Some(moved) = moved {
+            println!("moved application to {moved}");
+        }
+
+        Ok(())
+    }
+}
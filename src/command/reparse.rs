@@ -0,0 +1,182 @@
+use anyhow::{Context, Result, anyhow};
+use camino::Utf8Path as Path;
+use clap::Args;
+
+use crate::{
+    PlyConfig,
+    application::Application,
+    command::Run,
+    document::Document,
+    job::SalaryRange,
+    listing::{self, LISTINGS_DIR},
+    parse::Parser,
+    repository::{Repository, Store},
+};
+
+#[derive(Args)]
+pub struct Reparse {
+    /// The paths to the application documents to reparse, or their IDs
+    #[arg(required_unless_present("all"))]
+    pub applications: Vec<String>,
+
+    /// Reparse every application in the data directory with a listing snapshot
+    #[arg(long, conflicts_with("applications"))]
+    pub all: bool,
+
+    /// The parser to use, this will be inferred from the source or listing URL if not given
+    #[arg(value_enum, long, short)]
+    pub parser: Option<Parser>,
+
+    /// Print the reparsed applications to STDOUT instead of writing them
+    #[arg(long)]
+    pub print: bool,
+}
+
+impl Reparse {
+    /// The parsers to attempt, in order. Aggregator listings snapshot the source posting when it
+    /// could be followed, so the source URL's parser is tried before the listing URL's, and then
+    /// the parser the snapshot was taken with, e.g. one given to `ply to` for a local file.
+    fn parsers(
+        &self,
+        document: &Document<Application>,
+        snapshotted: Option<Parser>,
+    ) -> Vec<Parser> {
+        if let Some(parser) = &self.parser {
+            return vec![parser.to_owned()];
+        }
+
+        let job = &document.record.job;
+        let mut parsers = [&job.source_url, &job.listing_url]
+            .into_iter()
+            .flatten()
+            .filter_map(Parser::infer)
+            .filter(|parser| *parser != Parser::Unimplemented)
+            .collect::<Vec<_>>();
+
+        if let Some(parser) = snapshotted
+            && parser != Parser::Unimplemented
+            && !parsers.contains(&parser)
+        {
+            parsers.push(parser);
+        }
+
+        parsers
+    }
+
+    fn reparse(
+        &self,
+        document: &mut Document<Application>,
+        content: &str,
+        snapshotted: Option<Parser>,
+    ) -> Result<()> {
+        for parser in self.parsers(document, snapshotted) {
+            if let Ok(Some(role)) = parser.parse_role(content) {
+                let job = &mut document.record.job;
+                job.company = role.company;
+                job.title = role.title;
+                job.team = role.team;
+
+                // Keep keys added to the salary range by hand, e.g. its currency
+                let extra = job
+                    .salary_range
+                    .take()
+                    .map(|salary| salary.extra)
+                    .unwrap_or_default();
+                job.salary_range = role
+                    .salary_range
+                    .map(|salary| SalaryRange { extra, ..salary });
+
+                job.source_url = role.source_url.or(job.source_url.to_owned());
+
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!(
+            "no parser produced a result from the HTML snapshot"
+        ))
+    }
+}
+
+impl Reparse {
+    /// Reparse an application from its latest HTML snapshot, writing or printing the result.
+    fn reparse_document(
+        &self,
+        repository: &Repository,
+        content_dir: &Path,
+        mut document: Document<Application>,
+    ) -> Result<()> {
+        let path = document.path.to_owned().unwrap_or_default();
+        let latest = match document.record.job.id() {
+            Some(id) => listing::latest_html(content_dir, &id)?,
+            None => None,
+        };
+
+        let Some((snapshot, html_path)) = latest else {
+            if !self.all {
+                println!(
+                    "no HTML snapshot for '{}', skipping",
+                    document.record.pretty_print()
+                );
+            }
+
+            return Ok(());
+        };
+
+        let snapshotted = snapshot
+            .read()
+            .ok()
+            .and_then(|(header, _)| header)
+            .map(|header| header.parser);
+
+        let content = listing::read_html(&html_path)?;
+        self.reparse(&mut document, &content, snapshotted)
+            .context(format!("failed to reparse application at {path}"))?;
+
+        if self.print {
+            println!("{}", document.new_content()?);
+        } else {
+            let moved = repository
+                .update(&mut document)
+                .context(format!("failed to write reparsed application at {path}"))?;
+
+            println!(
+                "application for '{}' reparsed from {}",
+                document.record.pretty_print(),
+                html_path
+            );
+
+            if let Some(moved) = moved {
+                println!("moved application to {moved}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Run for Reparse {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let content_dir = config.data_dir.join(LISTINGS_DIR);
+        let repository = Repository::new(config);
+        let mut failed = 0;
+
+        // With --all, one application that can't be reparsed shouldn't stop the rest
+        for document in repository.find_all(&self.applications, self.all)? {
+            match self.reparse_document(&repository, &content_dir, document) {
+                Ok(()) => {}
+                Err(e) if self.all => {
+                    eprintln!("{e:#}");
+                    failed += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if failed > 0 {
+            return Err(anyhow!("failed to reparse {failed} application(s)"));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    fs,
+    io::{IsTerminal, stdout},
+};
+
+use anyhow::{Context, Result, anyhow};
+use camino::Utf8PathBuf as PathBuf;
+use chrono::{DateTime, Utc};
+use clap::Args;
+use serde::Serialize;
+
+use crate::{
+    PlyConfig,
+    application::{Application, TimelineEntry},
+    command::Run,
+    document::Document,
+    editor,
+    repository::{Repository, Store},
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const RED: &str = "\x1b[31m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Args)]
+pub struct Show {
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
+    pub application: String,
+
+    /// Show the application's job listing snapshot instead of the application
+    #[arg(long, conflicts_with("json"))]
+    pub listing: bool,
+
+    /// Open in $VISUAL or $EDITOR instead of printing to STDOUT
+    #[arg(long, conflicts_with("json"))]
+    pub open: bool,
+
+    /// Print the application and its timeline as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct ShowJson<'a> {
+    path: &'a PathBuf,
+    application: &'a Application,
+    timeline: Vec<TimelineEntry>,
+    days_since_last_activity: i64,
+    notes: Option<&'a str>,
+}
+
+fn style(text: &str, style: &str) -> String {
+    if stdout().is_terminal() {
+        format!("{style}{text}{RESET}")
+    } else {
+        text.to_owned()
+    }
+}
+
+fn days(days: i64) -> String {
+    if days == 1 {
+        "1 day".to_owned()
+    } else {
+        format!("{days} days")
+    }
+}
+
+impl Show {
+    fn render(
+        &self,
+        config: &PlyConfig,
+        document: &Document<Application>,
+        now: DateTime<Utc>,
+    ) -> String {
+        let record = &document.record;
+        let mut lines = vec![style(&record.pretty_print(), BOLD)];
+
+        if let Some(salary_range) = &record.job.salary_range {
+            lines.push(format!("Salary: {salary_range}"));
+        }
+
+        if let Some(cycle) = &record.cycle {
+            lines.push(format!("Cycle: {cycle}"));
+        }
+
+        if let Some(url) = &record.job.listing_url {
+            lines.push(format!("Listing: {url}"));
+        }
+
+        if let Some(url) = &record.job.source_url {
+            lines.push(format!("Source: {url}"));
+        }
+
+        lines.push(String::new());
+
+        for entry in record.timeline(now) {
+            let mut line = format!(
+                "{}  {:<12}",
+                entry.stage.start_time.format(DATE_FORMAT),
+                entry.stage.stage_type.to_string()
+            );
+
+            if let Some(name) = &entry.stage.name {
+                line.push_str(&format!(" {name}"));
+            }
+
+            if let Some(duration_days) = entry.duration_days {
+                line.push_str(&format!("  {}", days(duration_days)));
+            }
+
+            if let Some(deadline) = entry.stage.deadline {
+                let deadline = format!("deadline {}", deadline.format(DATE_FORMAT));
+                if entry.is_overdue {
+                    line.push_str(&format!(
+                        "  {}",
+                        style(&format!("{deadline} (overdue)"), RED)
+                    ));
+                } else {
+                    line.push_str(&format!("  {deadline}"));
+                }
+            }
+
+            lines.push(line);
+        }
+
+        let since_last_activity = (now - record.last_activity()).num_days();
+        if record.is_active() {
+            let mut line = format!("\n{} since last activity", days(since_last_activity));
+            if since_last_activity >= config.days_to_ghost.into() {
+                line.push_str(&style(", likely ghosted", RED));
+            }
+            lines.push(line);
+        }
+
+        if let Some(content) = &document.content
+            && !content.trim().is_empty()
+        {
+            lines.push(String::new());
+            lines.push(content.trim().to_owned());
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl Run for Show {
+    fn run(&self, config: &PlyConfig) -> Result<()> {
+        let document = Repository::new(config).find(&self.application)?;
+        let path = document
+            .path
+            .to_owned()
+            .context("application has no path")?;
+
+        if self.listing {
+            let listing = document
+                .record
+                .listing_snapshot_path(&config.data_dir)?
+                .ok_or(anyhow!(
+                    "application for '{}' has no listing snapshot",
+                    document.record.pretty_print()
+                ))?;
+
+            if self.open {
+                return editor::open(&listing);
+            }
+
+            print!(
+                "{}",
+                fs::read_to_string(&listing).context(format!("failed to read {listing}"))?
+            );
+
+            return Ok(());
+        }
+
+        if self.open {
+            return editor::open(&path);
+        }
+
+        let now = Utc::now();
+
+        if self.json {
+            let json = ShowJson {
+                path: &path,
+                application: &document.record,
+                timeline: document.record.timeline(now),
+                days_since_last_activity: (now - document.record.last_activity()).num_days(),
+                notes: document.content.as_deref(),
+            };
+
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json).context("failed to serialize application")?
+            );
+        } else {
+            println!("{}", self.render(config, &document, now));
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,20 @@
 use anyhow::{Context, Result, anyhow};
+use camino::Utf8PathBuf as PathBuf;
 use clap::Args;
-use std::ops::Not;
+use std::{fs, io, ops::Not};
 
 use crate::{
-    application, command::Run, config, document::Filename, job, parse::Parser,
+    application,
+    cache::{CacheMode, HttpCache},
+    command::Run,
+    config,
+    fetch::FetchOptions,
+    job,
+    listing::LISTINGS_DIR,
+    parse::Parser,
+    repository::{Repository, Store},
     scrape::ScrapedContent,
+    template,
 };
 use url::Url;
 
@@ -13,11 +23,32 @@ pub struct To {
     /// The URL of the job listing
     pub url: Option<String>,
 
-    /// The parser to use, this will be inferred when the given URL scheme is 'https' and required
-    /// if it is 'file'
+    /// The parser to use, this will be inferred when the given URL scheme is 'https' or 'http' and
+    /// required if it is 'file'
     #[arg(value_enum, long, short, requires("url"))]
     pub parser: Option<Parser>,
 
+    /// Allow fetching the listing over plain 'http', e.g. from a local or intranet server
+    #[arg(long, requires("url"))]
+    pub allow_http: bool,
+
+    /// Read the listing's HTML from STDIN instead of fetching the URL, e.g. for listings behind a
+    /// login or rendered with JavaScript
+    #[arg(long, requires("url"), conflicts_with("html"))]
+    pub stdin: bool,
+
+    /// Read the listing's HTML from a file saved from the browser instead of fetching the URL
+    #[arg(long, requires("url"))]
+    pub html: Option<PathBuf>,
+
+    /// Only use previously fetched responses from the cache instead of the network
+    #[arg(long, requires("url"), conflicts_with("refresh"))]
+    pub offline: bool,
+
+    /// Ignore previously fetched responses in the cache and fetch the listing again
+    #[arg(long, requires("url"))]
+    pub refresh: bool,
+
     /// The company for a new application, required if no listing URL is given
     #[arg(long, conflicts_with("url"), required_unless_present("url"))]
     pub company: Option<String>,
@@ -39,6 +70,18 @@ pub struct To {
     pub print: bool,
 }
 
+impl To {
+    fn cache_mode(&self) -> CacheMode {
+        if self.offline {
+            CacheMode::Offline
+        } else if self.refresh {
+            CacheMode::Refresh
+        } else {
+            CacheMode::default()
+        }
+    }
+}
+
 impl Run for To {
     fn run(&self, config: &config::PlyConfig) -> Result<()> {
         let cycle = self
@@ -51,17 +94,44 @@ impl Run for To {
             Some(url) => {
                 let url = Url::parse(url).context("failed to parse given URL")?;
 
-                let scraped = ScrapedContent::from_url(&url)
+                let mut options = FetchOptions {
+                    http: config.http.to_owned(),
+                    cache: Some(HttpCache::new(config, self.cache_mode())),
+                };
+                options.http.allow_http |= self.allow_http;
+
+                let parser = self.parser.to_owned();
+                let scraped = if self.stdin {
+                    let content = io::read_to_string(io::stdin())
+                        .context("failed to read listing HTML from STDIN")?;
+                    ScrapedContent::from_content(&url, content, parser, &options)
+                } else if let Some(html) = &self.html {
+                    let content = fs::read_to_string(html)
+                        .context(format!("failed to read listing HTML from {html}"))?;
+                    ScrapedContent::from_content(&url, content, parser, &options)
+                } else {
+                    ScrapedContent::from_url(&url, parser, &options)
+                };
+
+                let scraped = scraped
                     .and_then(|content| content.ok_or(anyhow!("no result from scraping URL")))
                     .context("failed to scrape URL")?;
 
-                let app = application::new(scraped.job.to_owned(), cycle);
+                let mut app = application::new(scraped.job.to_owned(), cycle);
 
                 // TODO: handle repeat applications to the same listing
-                if let Ok(filename) = scraped.job.filename() {
-                    scraped
-                        .snapshot(&config.data_dir.join("listings"), &filename)
+                if scraped.job.listing_url.is_some() {
+                    let snapshot = scraped
+                        .snapshot(
+                            &config.data_dir.join(LISTINGS_DIR),
+                            config.compress_snapshots,
+                        )
                         .context("failed to snapshot content")?;
+
+                    app.listing_snapshot = snapshot
+                        .strip_prefix(&config.data_dir)
+                        .ok()
+                        .map(|path| path.to_owned());
                 }
 
                 app
@@ -72,21 +142,26 @@ impl Run for To {
                     title: self.title.to_owned().unwrap(),
                     team: self.team.to_owned(),
                     listing_url: None,
+                    source_url: None,
                     salary_range: None,
+                    extra: toml::Table::new(),
                 };
 
                 application::new(job, cycle)
             }
         };
 
+        let mut document = application.new_document();
+        document.content = template::application_notes(config, &application)?;
+
         if self.print {
-            println!("{}", application.new_document().new_content()?);
+            println!("{}", document.new_content()?);
         } else {
-            application.write_new_document(config)?;
+            let path = Repository::new(config).create(&document)?;
             println!(
                 "application for '{}' created at {}",
                 application.pretty_print(),
-                application.filename()
+                path.strip_prefix(&config.data_dir).unwrap_or(&path)
             );
         }
 
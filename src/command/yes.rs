@@ -1,11 +1,15 @@
-use crate::{application, command::Run, config, document};
+use crate::{
+    application,
+    command::Run,
+    config, notes,
+    repository::{Repository, Store},
+};
 use anyhow::{Context, Result};
-use camino::Utf8Path as Path;
 use clap::Args;
 
 #[derive(Args)]
 pub struct Yes {
-    /// The path to the application document
+    /// The path to the application document, or its ID i.e. a unique prefix of its filename
     pub path: String,
 
     /// The next stage
@@ -14,11 +18,20 @@ pub struct Yes {
 
     /// The next stage deadline, this may be a date/timestamp or a natural language string e.g. "in 1 week"
     pub deadline: Option<String>,
+
+    /// A name for the next stage, e.g. "System Design", used as its heading in the notes
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// A note to add under the next stage, e.g. who the interviewers are
+    #[arg(long)]
+    pub note: Option<String>,
 }
 
 impl Run for Yes {
     fn run(&self, config: &config::PlyConfig) -> Result<()> {
-        let mut document = document::read::<application::Application>(Path::new(&self.path))?;
+        let repository = Repository::new(config);
+        let mut document = repository.find(&self.path)?;
         let now = chrono::Utc::now();
         let deadline = match &self.deadline {
             Some(deadline) => Some(
@@ -35,15 +48,22 @@ impl Run for Yes {
             None => None,
         };
 
-        document.record.stages.push(application::Stage {
+        let stage = application::Stage {
             start_time: now,
             deadline,
-            name: None,
+            name: self.name.to_owned(),
             stage_type: self.next_stage,
-        });
+            extra: toml::Table::new(),
+        };
+        document.record.stages.push(stage.to_owned());
+        notes::add_stage_section(config, &mut document, &stage)?;
 
-        document
-            .write(&config.data_dir)
+        if let Some(note) = &self.note {
+            notes::add_note(&mut document, note, now);
+        }
+
+        let moved = repository
+            .update(&mut document)
             .context("failed to write new stage to document")?;
 
         println!(
@@ -52,6 +72,10 @@ impl Run for Yes {
             self.next_stage
         );
 
+        if let Some(moved) = moved {
+            println!("moved application to {moved}");
+        }
+
         Ok(())
     }
 }
@@ -9,12 +9,36 @@ use serde::Serialize;
 
 const DATA_DIR: &str = "data";
 const DAYS_TO_GHOST: u16 = 90;
+const ARCHIVE_DIR: &str = "archive";
+const TEMPLATES_DIR: &str = "templates";
+const HTTP_TIMEOUT_SECS: u64 = 30;
+const HTTP_MAX_RETRIES: u32 = 3;
+const HTTP_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36";
 
 #[derive(Serialize, Deserialize)]
 pub struct PlyConfig {
     pub data_dir: PathBuf,
     pub days_to_ghost: u16,
     pub default_cycle: Option<String>,
+
+    /// Whether raw HTML listing snapshots are gzipped
+    pub compress_snapshots: bool,
+
+    /// Whether to keep the previous version of a document as a `.bak` whenever it's rewritten
+    pub keep_backups: bool,
+
+    /// Subdirectories of the data directory to file applications under, e.g. `{cycle}/{company}`,
+    /// built from the `{cycle}`, `{company}`, `{stage}` and `{year}` of each application
+    pub layout: Option<String>,
+
+    /// The directory within the data directory that `ply archive` moves closed applications into
+    pub archive_dir: PathBuf,
+
+    /// The directory of templates for the notes of new applications and stages, `templates/`
+    /// alongside this config by default
+    pub templates_dir: PathBuf,
+
+    pub http: HttpConfig,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -22,6 +46,38 @@ struct PartialPlyConfig {
     pub data_dir: Option<PathBuf>,
     pub days_to_ghost: Option<u16>,
     pub default_cycle: Option<String>,
+    pub compress_snapshots: Option<bool>,
+    pub keep_backups: Option<bool>,
+    pub layout: Option<String>,
+    pub archive_dir: Option<PathBuf>,
+    pub templates_dir: Option<PathBuf>,
+    pub http: Option<PartialHttpConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HttpConfig {
+    /// Timeout for an entire request, including reading the response body
+    pub timeout_secs: u64,
+
+    /// How many times to retry a request that was rate limited or hit a server error
+    pub max_retries: u32,
+
+    pub user_agent: String,
+
+    /// Whether plain `http` URLs may be fetched, off by default so listings aren't fetched in the clear
+    pub allow_http: bool,
+
+    /// Proxy to fetch through, taking precedence over the `HTTPS_PROXY` family of environment variables
+    pub proxy: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct PartialHttpConfig {
+    pub timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub user_agent: Option<String>,
+    pub allow_http: Option<bool>,
+    pub proxy: Option<String>,
 }
 
 pub fn default_config_path() -> PathBuf {
@@ -37,6 +93,24 @@ fn tilde_expand(dir: &Path) -> PathBuf {
     Path::new(&shellexpand::tilde(dir.as_str())).to_path_buf()
 }
 
+impl From<PartialHttpConfig> for HttpConfig {
+    fn from(config: PartialHttpConfig) -> HttpConfig {
+        HttpConfig {
+            timeout_secs: config.timeout_secs.unwrap_or(HTTP_TIMEOUT_SECS),
+            max_retries: config.max_retries.unwrap_or(HTTP_MAX_RETRIES),
+            user_agent: config.user_agent.unwrap_or(HTTP_USER_AGENT.to_owned()),
+            allow_http: config.allow_http.unwrap_or(false),
+            proxy: config.proxy,
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        PartialHttpConfig::default().into()
+    }
+}
+
 impl From<PartialPlyConfig> for PlyConfig {
     fn from(config: PartialPlyConfig) -> PlyConfig {
         let data_dir = tilde_expand(&config.data_dir.unwrap_or(Path::new(DATA_DIR).to_path_buf()));
@@ -45,6 +119,17 @@ impl From<PartialPlyConfig> for PlyConfig {
             data_dir,
             days_to_ghost: config.days_to_ghost.unwrap_or(DAYS_TO_GHOST),
             default_cycle: config.default_cycle,
+            compress_snapshots: config.compress_snapshots.unwrap_or(false),
+            keep_backups: config.keep_backups.unwrap_or(false),
+            layout: config.layout,
+            archive_dir: config
+                .archive_dir
+                .unwrap_or(Path::new(ARCHIVE_DIR).to_path_buf()),
+            templates_dir: config
+                .templates_dir
+                .map(|dir| tilde_expand(&dir))
+                .unwrap_or_else(|| default_config_path().with_file_name(TEMPLATES_DIR)),
+            http: config.http.unwrap_or_default().into(),
         }
     }
 }
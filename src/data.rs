@@ -1,12 +1,21 @@
-use std::{fmt::Display, fs::DirBuilder};
+use std::{
+    fmt::Display,
+    fs::{self, DirBuilder, File},
+    io::{ErrorKind, Write},
+};
 
-use anyhow::{Context, Error, Result};
+use anyhow::{Context, Error, Result, anyhow};
 use camino::Utf8Path as Path;
-use chrono::{DateTime, Utc};
+use camino::Utf8PathBuf as PathBuf;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use sha2::{Digest, Sha256};
 
+use crate::listing::LISTINGS_DIR;
+
 const TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S%3f";
 const HASH_ID_LENGTH: usize = 7;
+const BACKUP_EXTENSION: &str = "bak";
+pub const DOCUMENT_EXTENSION: &str = "md";
 
 pub fn normalize_filename_attr(name: &str) -> String {
     name.to_lowercase()
@@ -32,31 +41,327 @@ pub fn ensure_directory(dir: &Path) -> Result<()> {
         .context(format!("failed to build directory {dir}"))
 }
 
+/// Remove the directories above a path that are left empty, e.g. after the document at the path
+/// was moved away, up to but not including the root.
+pub fn remove_empty_parents(path: &Path, root: &Path) {
+    let Ok(root) = root.canonicalize_utf8() else {
+        return;
+    };
+
+    let mut dir = path.parent().and_then(|dir| dir.canonicalize_utf8().ok());
+
+    while let Some(current) = dir {
+        // Removing a directory fails if it isn't empty, which is where this stops
+        if current == root || !current.starts_with(&root) || fs::remove_dir(&current).is_err() {
+            break;
+        }
+
+        dir = current.parent().map(|parent| parent.to_owned());
+    }
+}
+
+/// Write a file in full to a temporary sibling, syncing it to disk before moving it into place so
+/// that the file is never left partially written. When not replacing, fails if the file exists.
+fn write_via_temp(path: &Path, content: &[u8], replace: bool) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let filename = path
+        .file_name()
+        .context(format!("failed to determine filename of {path}"))?;
+    let temp = dir.join(format!(".{filename}.{}.tmp", std::process::id()));
+
+    let result = File::create_new(&temp)
+        .and_then(|mut f| {
+            // Renaming takes the temporary file's permissions, so give it the original's
+            if replace && let Ok(metadata) = fs::metadata(path) {
+                f.set_permissions(metadata.permissions())?;
+            }
+
+            f.write_all(content)?;
+            f.sync_all()
+        })
+        .context(format!("failed to write temporary file {temp}"))
+        .and_then(|_| {
+            if replace {
+                fs::rename(&temp, path).context(format!("failed to move {temp} to {path}"))
+            } else {
+                move_new(&temp, path)
+            }
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+
+    result?;
+
+    // Persist the directory entry too, otherwise the rename itself may be lost on a crash
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .context(format!("failed to sync directory {dir}"))
+}
+
+/// Move a temporary file to a path that must not exist yet. Linking rather than renaming refuses to
+/// clobber an existing file, but some filesystems, e.g. exFAT and network shares, don't support
+/// links, reported as `EPERM` on Linux, so there the path is claimed before renaming over it.
+fn move_new(temp: &Path, path: &Path) -> Result<()> {
+    match fs::hard_link(temp, path) {
+        Ok(()) => fs::remove_file(temp).context(format!("failed to remove {temp}")),
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::Unsupported | ErrorKind::PermissionDenied
+            ) =>
+        {
+            claim_and_rename(temp, path)
+        }
+        Err(e) => Err(e).context(format!("failed to create {path}")),
+    }
+}
+
+/// Create the path empty, failing if it exists, and then rename the temporary file over it.
+fn claim_and_rename(temp: &Path, path: &Path) -> Result<()> {
+    File::create_new(path).context(format!("failed to create {path}"))?;
+
+    fs::rename(temp, path).map_err(|e| {
+        let _ = fs::remove_file(path);
+        Error::from(e).context(format!("failed to move {temp} to {path}"))
+    })
+}
+
+/// Atomically replace the file at the given path, optionally keeping its previous version
+/// alongside it with a `.bak` extension.
+pub fn write_atomic(path: &Path, content: &[u8], backup: bool) -> Result<()> {
+    if backup && path.is_file() {
+        let backup_path = PathBuf::from(format!("{path}.{BACKUP_EXTENSION}"));
+        fs::copy(path, &backup_path).context(format!(
+            "failed to back up {path} to {backup_path} before writing"
+        ))?;
+    }
+
+    write_via_temp(path, content, true)
+}
+
+/// Atomically write a new file at the given path, failing if it already exists.
+pub fn write_new_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    write_via_temp(path, content, false)
+}
+
+fn join_attrs(attrs: Vec<impl Display>) -> String {
+    attrs
+        .iter()
+        .map(|attr| normalize_filename_attr(&attr.to_string()))
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// Resolve a document given either its path or an ID, i.e. a unique prefix of its filename in the
+/// data directory such as its timestamp.
+pub fn resolve_document(data_dir: &Path, id_or_path: &str) -> Result<PathBuf> {
+    let path = Path::new(id_or_path);
+    if path.is_file() {
+        return Ok(path.to_owned());
+    }
+
+    let mut matches = document_paths(data_dir)?
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|filename| filename.starts_with(id_or_path))
+        })
+        .collect::<Vec<_>>();
+
+    match matches.len() {
+        0 => Err(anyhow!("no document found with path or ID '{id_or_path}'")),
+        1 => Ok(matches.remove(0)),
+        _ => Err(anyhow!(
+            "ID '{id_or_path}' is ambiguous, it matches:\n{}",
+            matches
+                .iter()
+                .map(|path| path.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        )),
+    }
+}
+
+/// Every document in the data directory and its subdirectories, sorted by path. Listing snapshots
+/// and hidden files and directories, like the HTTP cache, are skipped.
+pub fn document_paths(data_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    let mut dirs = vec![data_dir.to_owned()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in dir
+            .read_dir_utf8()
+            .context(format!("failed to read files in data directory {dir}"))?
+            .flatten()
+        {
+            let path = entry.path();
+
+            if entry.file_name().starts_with('.')
+                || (dir == data_dir && entry.file_name() == LISTINGS_DIR)
+            {
+                continue;
+            }
+
+            if path.is_dir() {
+                dirs.push(path.to_owned());
+            } else if path.extension() == Some(DOCUMENT_EXTENSION) && path.is_file() {
+                paths.push(path.to_owned());
+            }
+        }
+    }
+
+    paths.sort();
+
+    Ok(paths)
+}
+
 pub fn timestamp_filename(timestamp: &DateTime<Utc>, attrs: Vec<impl Display>) -> String {
     format!(
         "{}.{}.md",
         timestamp.format(TIMESTAMP_FORMAT),
-        attrs
-            .iter()
-            .map(|attr| normalize_filename_attr(&attr.to_string()))
-            .collect::<Vec<String>>()
-            .join(".")
+        join_attrs(attrs)
     )
 }
 
-pub fn id_filename(id: &str, attrs: Vec<impl Display>) -> String {
-    let hash = Sha256::digest(id);
+/// Parse a timestamp formatted the way it is in filenames.
+pub fn parse_filename_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, TIMESTAMP_FORMAT)
+        .ok()
+        .map(|timestamp| timestamp.and_utc())
+}
+
+pub fn id_hash(id: &str) -> String {
+    hex::encode(Sha256::digest(id))
+        .chars()
+        .take(HASH_ID_LENGTH)
+        .collect::<String>()
+}
 
+pub fn id_timestamp_filename(
+    id: &str,
+    timestamp: &DateTime<Utc>,
+    attrs: Vec<impl Display>,
+) -> String {
     format!(
-        "{}.{}.md",
-        hex::encode(hash)
-            .chars()
-            .take(HASH_ID_LENGTH)
-            .collect::<String>(),
-        attrs
-            .iter()
-            .map(|attr| normalize_filename_attr(&attr.to_string()))
-            .collect::<Vec<String>>()
-            .join(".")
+        "{}.{}.{}.md",
+        id_hash(id),
+        timestamp.format(TIMESTAMP_FORMAT),
+        join_attrs(attrs)
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory to write into, removed when dropped.
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new() -> Self {
+            let dir = PathBuf::try_from(std::env::temp_dir())
+                .unwrap()
+                .join(format!("ply-test-{:08x}", rand::random::<u32>()));
+            fs::create_dir(&dir).unwrap();
+
+            TestDir(dir)
+        }
+
+        fn entries(&self) -> Vec<String> {
+            let mut entries = self
+                .0
+                .read_dir_utf8()
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_owned())
+                .collect::<Vec<_>>();
+            entries.sort();
+
+            entries
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn remove_empty_parents_stops_at_root_and_non_empty_dirs() {
+        let dir = TestDir::new();
+        let emptied = dir.0.join("fall_2026/acme/team");
+        let kept = dir.0.join("spring_2027/acme");
+        fs::create_dir_all(&emptied).unwrap();
+        fs::create_dir_all(&kept).unwrap();
+        fs::write(dir.0.join("spring_2027/other.md"), "").unwrap();
+
+        remove_empty_parents(&emptied.join("application.md"), &dir.0);
+        remove_empty_parents(&kept.join("application.md"), &dir.0);
+
+        assert!(dir.0.is_dir());
+        assert_eq!(dir.entries(), vec!["spring_2027"]);
+        assert!(!kept.exists());
+        assert!(dir.0.join("spring_2027/other.md").is_file());
+    }
+
+    #[test]
+    fn write_new_atomic_refuses_to_clobber() {
+        let dir = TestDir::new();
+        let path = dir.0.join("application.md");
+
+        write_new_atomic(&path, b"first").unwrap();
+        assert!(write_new_atomic(&path, b"second").is_err());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        assert_eq!(dir.entries(), vec!["application.md"]);
+    }
+
+    #[test]
+    fn claim_and_rename_moves_into_place() {
+        let dir = TestDir::new();
+        let temp = dir.0.join(".application.md.tmp");
+        let path = dir.0.join("application.md");
+
+        fs::write(&temp, "content").unwrap();
+        claim_and_rename(&temp, &path).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "content");
+        assert_eq!(dir.entries(), vec!["application.md"]);
+    }
+
+    #[test]
+    fn claim_and_rename_refuses_to_clobber() {
+        let dir = TestDir::new();
+        let temp = dir.0.join(".application.md.tmp");
+        let path = dir.0.join("application.md");
+
+        fs::write(&path, "existing").unwrap();
+        fs::write(&temp, "content").unwrap();
+
+        assert!(claim_and_rename(&temp, &path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "existing");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TestDir::new();
+        let path = dir.0.join("application.md");
+
+        fs::write(&path, "before").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        write_atomic(&path, b"after", false).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "after");
+    }
+}
@@ -1,12 +1,11 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Error, Result, anyhow};
 use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
 use serde::{Serialize, de::DeserializeOwned};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader, Write},
-};
+use std::{fmt, fs};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
 
-use crate::data::ensure_directory;
+use crate::data::{document_paths, ensure_directory, write_atomic, write_new_atomic};
 
 pub trait Filename {
     fn filename(&self) -> String;
@@ -27,87 +26,491 @@ where
 pub struct Document<Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument> {
     pub record: Documentable,
     pub content: Option<String>,
+
+    /// The frontmatter as it was read, so that its comments and formatting can be preserved when
+    /// the record is written back
+    pub frontmatter: Option<DocumentMut>,
+
+    /// Whether the frontmatter was read with CRLF line endings, which are kept when written back
+    pub crlf: bool,
+
+    /// Whether the document started with a byte order mark, which is kept when written back
+    pub bom: bool,
+
+    /// Where the document was read from, which is where it's written back to
+    pub path: Option<PathBuf>,
+}
+
+/// A document whose frontmatter failed to deserialize, locating the error within the document.
+#[derive(Debug)]
+pub struct FrontmatterError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for FrontmatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path, line, self.message),
+            None => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+impl std::error::Error for FrontmatterError {}
+
+const DELIMITER: &str = "---";
+const BOM: char = '\u{feff}';
+
+/// A document split into its frontmatter and body. Only the first two delimiter lines count, so
+/// the body is free to contain its own `---`.
+pub struct Split<'a> {
+    pub frontmatter: &'a str,
+    pub body: &'a str,
+
+    /// Whether the delimiters end in CRLF rather than LF
+    pub crlf: bool,
+
+    /// Whether the text started with a byte order mark
+    pub bom: bool,
+}
+
+/// Split a document's text into its frontmatter and body, tolerating a leading BOM and CRLF line
+/// endings. The body is everything after the closing delimiter's line, byte-for-byte.
+pub fn split(text: &str) -> std::result::Result<Split<'_>, &'static str> {
+    let bom = text.starts_with(BOM);
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+
+    let (opening, mut rest) = text.split_once('\n').unwrap_or((text, ""));
+    if opening.trim() != DELIMITER {
+        return Err("missing frontmatter, expected the document to start with `---`");
+    }
+
+    let crlf = opening.ends_with('\r');
+    let frontmatter_start = rest;
+
+    loop {
+        if rest.is_empty() {
+            return Err("unterminated frontmatter, expected a closing `---`");
+        }
+
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+
+        if line.trim() == DELIMITER {
+            let frontmatter = &frontmatter_start[..frontmatter_start.len() - rest.len()];
+            return Ok(Split {
+                frontmatter,
+                body: next,
+                crlf,
+                bom,
+            });
+        }
+
+        rest = next;
+    }
 }
 
 pub fn read<Documentable>(filename: &Path) -> Result<Document<Documentable>>
 where
     Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument,
 {
-    let file = File::open(filename).context(format!("failed to open document at {}", filename))?;
-
-    let mut frontmatter = String::new();
-    let mut content = String::new();
-    let mut state = 0;
-
-    for line in BufReader::new(file).lines() {
-        let line = line.context(format!("failed to read line in document at {}", filename))?;
-
-        if line.trim() == "---" {
-            state += 1;
-        } else if state == 1 {
-            frontmatter.push_str(&line);
-            frontmatter.push('\n');
-        } else {
-            content.push_str(&line);
-            content.push('\n');
-        };
-    }
+    let text =
+        fs::read_to_string(filename).context(format!("failed to read document at {}", filename))?;
 
-    let record: Documentable = toml::from_str(&frontmatter).context(format!(
-        "failed to deserialize document at {} into TOML",
-        filename
-    ))?;
+    parse(&text, filename)
+}
+
+/// Parse a document's text, read from the given path.
+fn parse<Documentable>(text: &str, filename: &Path) -> Result<Document<Documentable>>
+where
+    Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument,
+{
+    let Split {
+        frontmatter,
+        body,
+        crlf,
+        bom,
+    } = split(text).map_err(|message| {
+        Error::new(FrontmatterError {
+            path: filename.to_owned(),
+            line: None,
+            message: message.to_owned(),
+        })
+    })?;
+
+    let frontmatter = frontmatter.replace("\r\n", "\n");
+
+    let record: Documentable = toml::from_str(&frontmatter).map_err(|e| {
+        // Line numbers are relative to the whole document rather than just its frontmatter, which
+        // starts on the second line
+        let line = e
+            .span()
+            .map(|span| 2 + frontmatter[..span.start].matches('\n').count());
+
+        Error::new(FrontmatterError {
+            path: filename.to_owned(),
+            line,
+            message: e.message().to_owned(),
+        })
+        .context(format!(
+            "failed to deserialize document at {} into TOML",
+            filename
+        ))
+    })?;
 
     Ok(Document::<Documentable> {
         record,
-        content: Some(content),
+        content: Some(body.to_owned()),
+        frontmatter: frontmatter.parse::<DocumentMut>().ok(),
+        crlf,
+        bom,
+        path: Some(filename.to_owned()),
     })
 }
 
+/// Every document in a directory and its subdirectories, split into those that could be read and those that couldn't.
+pub struct ReadAll<Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument> {
+    pub documents: Vec<(PathBuf, Document<Documentable>)>,
+    pub failures: Vec<(PathBuf, Error)>,
+}
+
+pub fn read_all<Documentable>(dir: &Path) -> Result<ReadAll<Documentable>>
+where
+    Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument,
+{
+    let paths = document_paths(dir)?;
+
+    let mut all = ReadAll {
+        documents: vec![],
+        failures: vec![],
+    };
+
+    for path in paths {
+        match read(&path) {
+            Ok(document) => all.documents.push((path, document)),
+            Err(e) => all.failures.push((path, e)),
+        }
+    }
+
+    Ok(all)
+}
+
+impl<Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument>
+    ReadAll<Documentable>
+{
+    /// The documents that could be read, with the rest handled by [`skip_failures`].
+    pub fn into_documents(self, strict: bool) -> Result<Vec<(PathBuf, Document<Documentable>)>> {
+        skip_failures(self.failures, strict)?;
+
+        Ok(self.documents)
+    }
+}
+
+/// Fail on the first document that couldn't be read if strict, otherwise warn about how many were
+/// skipped.
+pub fn skip_failures(failures: Vec<(PathBuf, Error)>, strict: bool) -> Result<()> {
+    let skipped = failures.len();
+
+    if let Some((path, e)) = failures.into_iter().next() {
+        if strict {
+            return Err(e.context(format!("failed to read document at {path}")));
+        }
+
+        eprintln!(
+            "warning: skipped {skipped} unreadable document(s), run `ply doctor` for details"
+        );
+    }
+
+    Ok(())
+}
+
 impl<Documentable: Serialize + DeserializeOwned + Filename + Clone + PreDocument>
     Document<Documentable>
 {
     pub fn new_content(&self) -> Result<String> {
         let record = self.record.pre_document();
-        let frontmatter =
+        let mut frontmatter =
             toml::to_string(&record).context("failed to serialize frontmatter for document")?;
 
+        if let Some(original) = &self.frontmatter {
+            let updated = frontmatter
+                .parse::<DocumentMut>()
+                .context("failed to parse serialized frontmatter for document")?;
+
+            let mut merged = original.to_owned();
+            merge_table(merged.as_table_mut(), updated.as_table());
+            frontmatter = merged.to_string();
+        }
+
+        let mut header = format!("{DELIMITER}\n{frontmatter}{DELIMITER}\n");
+        if self.crlf {
+            header = header.replace("\r\n", "\n").replace('\n', "\r\n");
+        }
+
+        if self.bom {
+            header.insert(0, BOM);
+        }
+
         Ok(format!(
-            "---\n{frontmatter}---\n{}",
+            "{header}{}",
             self.content.clone().unwrap_or("".to_owned())
         ))
     }
 
-    pub fn write_new(&self, dir: &Path) -> Result<File> {
+    pub fn write_new(&self, dir: &Path) -> Result<PathBuf> {
         ensure_directory(dir)?;
 
-        let filename = self.record.filename();
-        let mut f = File::create_new(dir.join(filename))
-            .context(format!("failed to create document at {}", dir.as_str()))?;
+        let path = dir.join(self.record.filename());
+        write_new_atomic(&path, self.new_content()?.as_bytes())
+            .context(format!("failed to write document at {path}"))?;
+
+        Ok(path)
+    }
+
+    /// Write the document back to where it was read from, keeping the previous version as a `.bak`
+    /// if `backup`
+    pub fn write(&self, backup: bool) -> Result<PathBuf> {
+        let path = self
+            .path
+            .to_owned()
+            .context("failed to write document, it has no path to be written back to")?;
+
+        write_atomic(&path, self.new_content()?.as_bytes(), backup)
+            .context(format!("failed to write document at {path}"))?;
+
+        Ok(path)
+    }
+
+    /// The path the document should have given its record, in the same directory it's in now
+    pub fn normalized_path(&self) -> Option<PathBuf> {
+        self.path
+            .as_ref()
+            .map(|path| path.with_file_name(self.record.filename()))
+    }
+
+    /// Rename the document to match its record, returning the new path if it was renamed.
+    pub fn normalize(&mut self) -> Result<Option<PathBuf>> {
+        let (Some(path), Some(normalized)) = (&self.path, self.normalized_path()) else {
+            return Ok(None);
+        };
+
+        if *path == normalized {
+            return Ok(None);
+        }
+
+        if normalized.try_exists().context(format!(
+            "failed to determine if a document already exists at {normalized}"
+        ))? {
+            return Err(anyhow!(
+                "failed to rename {path} to {normalized}, a document already exists there"
+            ));
+        }
 
-        f.write_all(self.new_content()?.as_bytes())
-            .context("failed to write document")?;
+        fs::rename(path, &normalized)
+            .context(format!("failed to rename {path} to {normalized}"))?;
+        self.path = Some(normalized.to_owned());
 
-        Ok(f)
+        Ok(Some(normalized))
     }
 
-    pub fn write(&self, dir: &Path) -> Result<File> {
+    /// Move the document into the given directory, keeping its filename, returning the new path if
+    /// it was moved.
+    pub fn move_to(&mut self, dir: &Path) -> Result<Option<PathBuf>> {
+        let Some(path) = &self.path else {
+            return Ok(None);
+        };
+
+        let filename = path
+            .file_name()
+            .context(format!("failed to determine filename of {path}"))?;
+        let moved = dir.join(filename);
+
+        if moved.try_exists().context(format!(
+            "failed to determine if a document already exists at {moved}"
+        ))? {
+            if path.canonicalize_utf8().ok() == moved.canonicalize_utf8().ok() {
+                return Ok(None);
+            }
+
+            return Err(anyhow!(
+                "failed to move {path} to {moved}, a document already exists there"
+            ));
+        }
+
         ensure_directory(dir)?;
+        fs::rename(path, &moved).context(format!("failed to move {path} to {moved}"))?;
+        self.path = Some(moved.to_owned());
 
-        let filename = self.record.filename();
-        let mut f = File::create(dir.join(filename)).context("failed to create document")?;
-        let record = self.record.pre_document();
-        let frontmatter =
-            toml::to_string(&record).context("failed to serialize frontmatter for document")?;
+        Ok(Some(moved))
+    }
+}
 
-        let content = format!(
-            "---\n{frontmatter}---\n{}",
-            self.content.clone().unwrap_or("".to_owned())
-        );
+/// Update the original table in place to match the updated one, so that unchanged keys keep their
+/// position, formatting and comments.
+fn merge_table(original: &mut dyn TableLike, updated: &dyn TableLike) {
+    let removed = original
+        .iter()
+        .filter(|(key, _)| !updated.contains_key(key))
+        .map(|(key, _)| key.to_owned())
+        .collect::<Vec<_>>();
+
+    for key in removed {
+        original.remove(&key);
+    }
+
+    for (key, item) in updated.iter() {
+        match original.get_mut(key) {
+            Some(existing) => merge_item(existing, item),
+            None => {
+                original.insert(key, item.to_owned());
+            }
+        }
+    }
+}
+
+fn merge_item(original: &mut Item, updated: &Item) {
+    match (original, updated) {
+        (Item::Table(original), Item::Table(updated)) => merge_table(original, updated),
+        (Item::Value(Value::InlineTable(original)), Item::Table(updated)) => {
+            merge_table(original, updated)
+        }
+        (Item::ArrayOfTables(original), Item::ArrayOfTables(updated)) => {
+            for (i, table) in updated.iter().enumerate() {
+                match original.get_mut(i) {
+                    Some(existing) => merge_table(existing, table),
+                    None => original.push(table.to_owned()),
+                }
+            }
+
+            while original.len() > updated.len() {
+                original.remove(original.len() - 1);
+            }
+        }
+        (Item::Value(original), Item::Value(updated)) => {
+            if !same_value(original, updated) {
+                let decor = original.decor().to_owned();
+                *original = updated.to_owned();
+                *original.decor_mut() = decor;
+            }
+        }
+        (original, updated) => *original = updated.to_owned(),
+    }
+}
+
+/// Whether two values are semantically equal regardless of how they're formatted.
+fn same_value(a: &Value, b: &Value) -> bool {
+    let parse = |value: &Value| {
+        let mut value = value.to_owned();
+        value.decor_mut().clear();
+        format!("value = {value}").parse::<toml::Table>().ok()
+    };
+
+    parse(a).is_some_and(|a| Some(a) == parse(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone)]
+    struct Record {
+        title: String,
+        count: u32,
+
+        #[serde(flatten)]
+        extra: toml::Table,
+    }
+
+    impl Filename for Record {
+        fn filename(&self) -> String {
+            self.title.to_owned()
+        }
+    }
+
+    impl PreDocument for Record {}
+
+    fn frontmatter() -> impl Strategy<Value = String> {
+        (
+            "[a-zA-Z0-9 ]{0,16}",
+            any::<u32>(),
+            proptest::option::of("[a-z ]{0,16}"),
+            proptest::option::of("[a-z]{1,8}"),
+        )
+            .prop_map(|(title, count, comment, extra)| {
+                let mut frontmatter = String::new();
+                if let Some(comment) = &comment {
+                    frontmatter.push_str(&format!("# {comment}\n"));
+                }
+
+                frontmatter.push_str(&format!("title = \"{title}\"\ncount = {count}\n"));
+
+                if let Some(extra) = extra {
+                    frontmatter.push_str(&format!("\n[extra]\nkey = \"{extra}\" # by hand\n"));
+                }
+
+                frontmatter
+            })
+    }
+
+    /// Bodies made of lines that are likely to trip up splitting, like delimiters and CRLF.
+    fn body() -> impl Strategy<Value = String> {
+        let line = prop_oneof![
+            Just("---".to_owned()),
+            Just("--- ".to_owned()),
+            Just("## Notes".to_owned()),
+            Just(String::new()),
+            "[^\r\n]{0,24}",
+        ];
+        let ending = prop_oneof![Just("\n"), Just("\r\n")];
+
+        proptest::collection::vec((line, ending), 0..8)
+            .prop_map(|lines| lines.into_iter().map(|(line, end)| line + end).collect())
+    }
+
+    fn document(frontmatter: &str, body: &str, crlf: bool, bom: bool) -> String {
+        let mut text = format!("---\n{frontmatter}---\n");
+        if crlf {
+            text = text.replace('\n', "\r\n");
+        }
+
+        if bom {
+            text.insert(0, BOM);
+        }
+
+        text + body
+    }
+
+    proptest! {
+        #[test]
+        fn split_keeps_body(
+            frontmatter in frontmatter(),
+            body in body(),
+            crlf: bool,
+            bom: bool,
+        ) {
+            let text = document(&frontmatter, &body, crlf, bom);
+            let split = split(&text).unwrap();
+
+            prop_assert_eq!(split.body, body.as_str());
+            prop_assert_eq!(split.crlf, crlf);
+            prop_assert_eq!(split.bom, bom);
+        }
 
-        f.write_all(content.as_bytes())
-            .context("failed to write document")?;
+        #[test]
+        fn read_then_write_is_identity(
+            frontmatter in frontmatter(),
+            body in body(),
+            crlf: bool,
+            bom: bool,
+        ) {
+            let text = document(&frontmatter, &body, crlf, bom);
+            let document = parse::<Record>(&text, Path::new("record.md")).unwrap();
 
-        Ok(f)
+            prop_assert_eq!(document.new_content().unwrap(), text);
+        }
     }
 }
@@ -0,0 +1,88 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    process::Command,
+};
+
+use anyhow::{Context, Result, anyhow};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Open the file at the given path in the user's `$VISUAL` or `$EDITOR`, waiting for it to exit.
+pub fn open(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or(DEFAULT_EDITOR.to_owned());
+
+    // Editors are commonly configured with arguments, e.g. `code --wait`
+    let mut args = editor.split_whitespace();
+    let program = args.next().ok_or(anyhow!("editor command is empty"))?;
+
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .context(format!("failed to launch editor {editor}"))?;
+
+    if !status.success() {
+        return Err(anyhow!("editor {editor} exited with {status}"));
+    }
+
+    Ok(())
+}
+
+/// A private file to write in the editor, removed when it's dropped so it's cleaned up however the
+/// edit ends.
+pub struct Scratch {
+    pub path: PathBuf,
+}
+
+impl Scratch {
+    /// Create a scratch file with the given content next to the document at the given path. It's
+    /// hidden, so it isn't taken for a document, and has a random suffix so concurrent edits don't
+    /// collide. It's created fresh rather than opened, so an existing file or symlink is never
+    /// written through.
+    pub fn new(document: &Path, purpose: &str, content: &str) -> Result<Self> {
+        let dir = document
+            .parent()
+            .filter(|dir| !dir.as_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let stem = document.file_stem().unwrap_or("application");
+        let path = dir.join(format!(
+            ".{stem}.{purpose}.{:08x}.md",
+            rand::random::<u32>()
+        ));
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options
+            .open(&path)
+            .context(format!("failed to create scratch file {path}"))?;
+
+        // Hold on to it from here so it's removed even if writing fails
+        let scratch = Scratch { path };
+
+        file.write_all(content.as_bytes())
+            .context(format!("failed to write scratch file {}", scratch.path))?;
+
+        Ok(scratch)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path)
+            && e.kind() != ErrorKind::NotFound
+        {
+            eprintln!("warning: failed to remove {}: {e}", self.path);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use std::{fs, io::Write};
+
+use anyhow::{Context, Result};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use rusqlite::{Connection, params};
+use serde::Serialize;
+
+use crate::{application::Application, document::Document};
+
+#[derive(Copy, Clone, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+    Jsonl,
+}
+
+/// An application flattened into a single row. Its columns are also what `ply import` maps by
+/// default, so that exports can be imported again.
+#[derive(Serialize)]
+pub struct ApplicationRow {
+    pub path: String,
+    pub company: String,
+    pub title: String,
+    pub team: Option<String>,
+    pub cycle: Option<String>,
+    pub applied_at: DateTime<Utc>,
+    pub current_stage: Option<String>,
+    pub stage_started_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    pub salary_lower: Option<u32>,
+    pub salary_upper: Option<u32>,
+    pub listing_url: Option<String>,
+    pub source_url: Option<String>,
+}
+
+/// A stage of an application flattened into a single row along with the application's job.
+#[derive(Serialize)]
+pub struct StageRow {
+    pub path: String,
+    pub company: String,
+    pub title: String,
+    pub team: Option<String>,
+    pub cycle: Option<String>,
+    pub position: usize,
+    pub stage_type: String,
+    pub name: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub deadline: Option<DateTime<Utc>>,
+    pub duration_days: Option<i64>,
+    pub is_current: bool,
+    pub is_overdue: bool,
+}
+
+impl ApplicationRow {
+    pub fn new(path: &Path, application: &Application) -> Self {
+        let job = &application.job;
+        let current_stage = application.current_stage();
+
+        ApplicationRow {
+            path: path.to_string(),
+            company: job.company.to_owned(),
+            title: job.title.to_owned(),
+            team: job.team.to_owned(),
+            cycle: application.cycle.to_owned(),
+            applied_at: application.applied_at,
+            current_stage: current_stage
+                .as_ref()
+                .map(|stage| stage.stage_type.to_string()),
+            stage_started_at: current_stage.map(|stage| stage.start_time),
+            is_active: application.is_active(),
+            salary_lower: job.salary_range.as_ref().map(|salary| salary.lower),
+            salary_upper: job.salary_range.as_ref().and_then(|salary| salary.upper()),
+            listing_url: job.listing_url.as_ref().map(|url| url.to_string()),
+            source_url: job.source_url.as_ref().map(|url| url.to_string()),
+        }
+    }
+}
+
+impl StageRow {
+    pub fn all(path: &Path, application: &Application, now: DateTime<Utc>) -> Vec<Self> {
+        let job = &application.job;
+
+        application
+            .timeline(now)
+            .into_iter()
+            .enumerate()
+            .map(|(position, entry)| StageRow {
+                path: path.to_string(),
+                company: job.company.to_owned(),
+                title: job.title.to_owned(),
+                team: job.team.to_owned(),
+                cycle: application.cycle.to_owned(),
+                position,
+                stage_type: entry.stage.stage_type.to_string(),
+                name: entry.stage.name,
+                start_time: entry.stage.start_time,
+                ended_at: entry.ended_at,
+                deadline: entry.stage.deadline,
+                duration_days: entry.duration_days,
+                is_current: entry.is_current,
+                is_overdue: entry.is_overdue,
+            })
+            .collect()
+    }
+}
+
+/// Write rows in the given format.
+pub fn rows<Row: Serialize>(writer: impl Write, rows: &[Row], format: Format) -> Result<()> {
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            for row in rows {
+                writer.serialize(row).context("failed to write CSV row")?;
+            }
+
+            writer.flush().context("failed to write CSV")
+        }
+        Format::Json => {
+            let mut writer = writer;
+            serde_json::to_writer_pretty(&mut writer, rows).context("failed to write JSON")?;
+            writeln!(writer).context("failed to write JSON")
+        }
+        Format::Jsonl => {
+            let mut writer = writer;
+            for row in rows {
+                serde_json::to_writer(&mut writer, row).context("failed to write JSON line")?;
+                writeln!(writer).context("failed to write JSON line")?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+const SCHEMA: &str = "
+CREATE TABLE cycles (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE jobs (
+    id INTEGER PRIMARY KEY,
+    company TEXT NOT NULL,
+    title TEXT NOT NULL,
+    team TEXT,
+    listing_url TEXT,
+    source_url TEXT
+);
+
+CREATE TABLE salaries (
+    job_id INTEGER PRIMARY KEY REFERENCES jobs (id),
+    lower INTEGER NOT NULL,
+    upper INTEGER
+);
+
+CREATE TABLE applications (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE,
+    job_id INTEGER NOT NULL REFERENCES jobs (id),
+    cycle_id INTEGER REFERENCES cycles (id),
+    applied_at TEXT NOT NULL,
+    current_stage TEXT,
+    is_active INTEGER NOT NULL,
+    last_activity TEXT NOT NULL,
+    listing_snapshot TEXT,
+    notes TEXT
+);
+
+CREATE TABLE stages (
+    id INTEGER PRIMARY KEY,
+    application_id INTEGER NOT NULL REFERENCES applications (id),
+    position INTEGER NOT NULL,
+    stage_type TEXT NOT NULL,
+    name TEXT,
+    start_time TEXT NOT NULL,
+    ended_at TEXT,
+    deadline TEXT,
+    duration_days INTEGER,
+    is_current INTEGER NOT NULL,
+    is_overdue INTEGER NOT NULL
+);
+
+CREATE INDEX stages_application_id ON stages (application_id);
+";
+
+/// Write every application into a new SQLite database at the given path, with a table for each of
+/// applications, jobs, stages, salaries and cycles. Stages are ordered by their `position` and
+/// their durations are computed as of `now`. The database is built alongside the path and then
+/// moved into place, replacing any existing database, so readers never see it half-written.
+pub fn sqlite(
+    path: &Path,
+    documents: &[(PathBuf, Document<Application>)],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let temp = PathBuf::from(format!("{path}.tmp"));
+    if temp.is_file() {
+        fs::remove_file(&temp).context(format!("failed to remove stale database {temp}"))?;
+    }
+
+    let mut connection =
+        Connection::open(&temp).context(format!("failed to create database at {temp}"))?;
+
+    connection
+        .execute_batch(SCHEMA)
+        .context("failed to create database tables")?;
+
+    let transaction = connection
+        .transaction()
+        .context("failed to begin database transaction")?;
+
+    for (path, document) in documents {
+        insert(&transaction, path, document, now)
+            .context(format!("failed to export application at {path}"))?;
+    }
+
+    transaction
+        .commit()
+        .context("failed to commit database transaction")?;
+    connection
+        .close()
+        .map_err(|(_, e)| e)
+        .context(format!("failed to close database at {temp}"))?;
+
+    fs::rename(&temp, path).context(format!("failed to move {temp} to {path}"))
+}
+
+fn insert(
+    connection: &Connection,
+    path: &Path,
+    document: &Document<Application>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let application = &document.record;
+    let job = &application.job;
+
+    connection.execute(
+        "INSERT INTO jobs (company, title, team, listing_url, source_url) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            job.company,
+            job.title,
+            job.team,
+            job.listing_url.as_ref().map(|url| url.as_str()),
+            job.source_url.as_ref().map(|url| url.as_str()),
+        ],
+    )?;
+    let job_id = connection.last_insert_rowid();
+
+    if let Some(salary_range) = &job.salary_range {
+        connection.execute(
+            "INSERT INTO salaries (job_id, lower, upper) VALUES (?1, ?2, ?3)",
+            params![job_id, salary_range.lower, salary_range.upper()],
+        )?;
+    }
+
+    let cycle_id = match &application.cycle {
+        Some(cycle) => {
+            connection.execute(
+                "INSERT OR IGNORE INTO cycles (name) VALUES (?1)",
+                params![cycle],
+            )?;
+
+            Some(connection.query_row(
+                "SELECT id FROM cycles WHERE name = ?1",
+                params![cycle],
+                |row| row.get::<_, i64>(0),
+            )?)
+        }
+        None => None,
+    };
+
+    connection.execute(
+        "INSERT INTO applications (path, job_id, cycle_id, applied_at, current_stage, is_active, last_activity, listing_snapshot, notes)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            path.as_str(),
+            job_id,
+            cycle_id,
+            application.applied_at.to_rfc3339(),
+            application
+                .current_stage()
+                .map(|stage| stage.stage_type.to_string()),
+            application.is_active(),
+            application.last_activity().to_rfc3339(),
+            application
+                .listing_snapshot
+                .as_ref()
+                .map(|snapshot| snapshot.as_str()),
+            document.content.as_deref().filter(|content| !content.trim().is_empty()),
+        ],
+    )?;
+    let application_id = connection.last_insert_rowid();
+
+    for (position, entry) in application.timeline(now).iter().enumerate() {
+        connection.execute(
+            "INSERT INTO stages (application_id, position, stage_type, name, start_time, ended_at, deadline, duration_days, is_current, is_overdue)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                application_id,
+                position,
+                entry.stage.stage_type.to_string(),
+                entry.stage.name,
+                entry.stage.start_time.to_rfc3339(),
+                entry.ended_at.map(|ended_at| ended_at.to_rfc3339()),
+                entry.stage.deadline.map(|deadline| deadline.to_rfc3339()),
+                entry.duration_days,
+                entry.is_current,
+                entry.is_overdue,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use scraper::{ElementRef, Html, Selector};
+
+/// Elements whose text never counts towards the main content of a page.
+const BOILERPLATE_TAGS: [&str; 7] = [
+    "nav", "header", "footer", "aside", "form", "script", "style",
+];
+
+/// Text blocks shorter than this are likely to be links, labels or buttons rather than prose.
+const MIN_BLOCK_LENGTH: usize = 25;
+
+/// Find the element most likely to hold the main content of a page, readability-style: every
+/// block of prose scores its parent by its length and its grandparent by half that, and the
+/// highest scoring element wins.
+pub fn main_content(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let block_selector = Selector::parse("p, li, pre, blockquote").unwrap();
+    let mut scores: HashMap<_, f64> = HashMap::new();
+
+    for block in document.select(&block_selector) {
+        if is_boilerplate(&block) {
+            continue;
+        }
+
+        let length = block.text().collect::<String>().trim().len();
+        if length < MIN_BLOCK_LENGTH {
+            continue;
+        }
+
+        let mut ancestors = block.ancestors().filter_map(ElementRef::wrap);
+
+        if let Some(parent) = ancestors.next() {
+            *scores.entry(parent.id()).or_default() += length as f64;
+        }
+
+        if let Some(grandparent) = ancestors.next() {
+            *scores.entry(grandparent.id()).or_default() += length as f64 / 2.0;
+        }
+    }
+
+    scores
+        .into_iter()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .and_then(|(id, _)| document.tree.get(id))
+        .and_then(ElementRef::wrap)
+        .map(|element| element.html())
+}
+
+fn is_boilerplate(element: &ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .any(|ancestor| BOILERPLATE_TAGS.contains(&ancestor.value().name()))
+}
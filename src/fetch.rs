@@ -1,26 +1,74 @@
 use anyhow::{Context, Error, Result, anyhow};
-use std::fs::read_to_string;
+use chrono::{DateTime, Utc};
+use std::{fmt, fs::read_to_string, thread::sleep, time::Duration};
+use ureq::{Agent, Proxy, ResponseExt, http::Response};
 use url::Url;
 
+use crate::{
+    cache::{CacheMode, CachedResponse, HttpCache},
+    config::HttpConfig,
+};
+
+const RETRY_BASE_DELAY_SECS: u64 = 1;
+const RETRY_MAX_DELAY_SECS: u64 = 60;
+
 pub enum Source {
     Http(HttpSource),
     LocalFile(LocalFileSource),
 }
 
-impl TryFrom<&Url> for Source {
-    fn try_from(value: &Url) -> std::result::Result<Self, Self::Error> {
-        match value.scheme() {
+/// A response that was unsuccessful even after any retries.
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub url: Url,
+    pub final_url: String,
+    pub status: u16,
+    pub attempts: u32,
+}
+
+impl fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to fetch source at {} over HTTP, got status {} from {} after {} attempt(s)",
+            self.url, self.status, self.final_url, self.attempts
+        )
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// Everything beyond a URL that determines how a source is fetched.
+#[derive(Clone, Default)]
+pub struct FetchOptions {
+    pub http: HttpConfig,
+    pub cache: Option<HttpCache>,
+}
+
+impl Source {
+    pub fn new(url: &Url, options: &FetchOptions) -> Result<Self> {
+        let http = &options.http;
+
+        match url.scheme() {
             "https" => Ok(Source::Http(HttpSource {
-                url: value.to_owned(),
+                url: url.to_owned(),
+                config: http.to_owned(),
+                cache: options.cache.to_owned(),
+            })),
+            "http" if http.allow_http => Ok(Source::Http(HttpSource {
+                url: url.to_owned(),
+                config: http.to_owned(),
+                cache: options.cache.to_owned(),
             })),
+            "http" => Err(anyhow!(
+                "refusing to fetch {url} over plain HTTP, set `allow_http` in the [http] config or pass --allow-http"
+            )),
             "file" => Ok(Source::LocalFile(LocalFileSource {
-                url: value.to_owned(),
+                url: url.to_owned(),
             })),
             scheme => Err(anyhow!("failed to determine source for URL sceme {scheme}")),
         }
     }
-
-    type Error = Error;
 }
 
 impl Fetch for Source {
@@ -34,23 +82,8 @@ impl Fetch for Source {
 
 pub struct HttpSource {
     url: Url,
-}
-
-impl TryFrom<&Url> for HttpSource {
-    fn try_from(value: &Url) -> std::result::Result<Self, Self::Error> {
-        if value.scheme() != "https" {
-            return Err(anyhow!(
-                "incorrect URL scheme, expected https but got {}",
-                value.scheme()
-            ));
-        }
-
-        Ok(HttpSource {
-            url: value.to_owned(),
-        })
-    }
-
-    type Error = Error;
+    config: HttpConfig,
+    cache: Option<HttpCache>,
 }
 
 pub struct LocalFileSource {
@@ -78,17 +111,171 @@ pub trait Fetch {
     fn fetch(&self) -> Result<String>;
 }
 
+impl HttpSource {
+    fn agent(&self) -> Result<Agent> {
+        Ok(Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(self.config.timeout_secs)))
+            .user_agent(&self.config.user_agent)
+            .http_status_as_error(false)
+            .proxy(self.proxy()?)
+            .build()
+            .into())
+    }
+
+    /// The proxy for this source's URL, taken from the config or else the environment, unless the
+    /// host is excluded by `NO_PROXY`.
+    fn proxy(&self) -> Result<Option<Proxy>> {
+        let host = self.url.host_str().unwrap_or_default();
+        if env_var(&["NO_PROXY", "no_proxy"]).is_some_and(|no_proxy| is_no_proxy(&no_proxy, host)) {
+            return Ok(None);
+        }
+
+        let env_vars: &[&str] = match self.url.scheme() {
+            "http" => &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"],
+            _ => &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"],
+        };
+
+        self.config
+            .proxy
+            .to_owned()
+            .or_else(|| env_var(env_vars))
+            .map(|proxy| Proxy::new(&proxy).context(format!("failed to parse proxy {proxy}")))
+            .transpose()
+    }
+
+    fn is_retryable<B>(response: &Response<B>) -> bool {
+        let status = response.status();
+        status.as_u16() == 429 || status.is_server_error()
+    }
+
+    /// The delay before the given retry attempt, preferring the server's `Retry-After` header
+    /// over exponential backoff.
+    fn retry_delay<B>(response: &Response<B>, attempt: u32) -> Duration {
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                value.trim().parse::<u64>().ok().or_else(|| {
+                    DateTime::parse_from_rfc2822(value).ok().map(|date| {
+                        (date.with_timezone(&Utc) - Utc::now()).num_seconds().max(0) as u64
+                    })
+                })
+            });
+
+        let backoff = RETRY_BASE_DELAY_SECS.saturating_mul(2u64.saturating_pow(attempt));
+
+        Duration::from_secs(retry_after.unwrap_or(backoff).min(RETRY_MAX_DELAY_SECS))
+    }
+}
+
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Whether the host matches an entry in a `NO_PROXY` list, either exactly or as a subdomain.
+fn is_no_proxy(no_proxy: &str, host: &str) -> bool {
+    let host = host.to_lowercase();
+
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.').to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{entry}")))
+}
+
+fn header<B>(response: &Response<B>, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_owned())
+}
+
 impl Fetch for HttpSource {
     fn fetch(&self) -> Result<String> {
-        ureq::get(self.url.as_str())
-            .call()?
-            .body_mut()
-            .read_to_string()
-            .context("failed to read HTTP response to string")
-            .context(format!(
-                "failed to fetch source at {} with HTTPS",
-                self.url.as_str()
-            ))
+        let cached = match &self.cache {
+            Some(cache) if cache.mode != CacheMode::Refresh => cache.get(&self.url)?,
+            _ => None,
+        };
+
+        if let Some(cache) = &self.cache
+            && cache.mode == CacheMode::Offline
+        {
+            return cached
+                .map(|cached| cached.body)
+                .ok_or_else(|| anyhow!("no cached response for {} while offline", self.url));
+        }
+
+        let agent = self.agent()?;
+        let mut attempt = 0;
+
+        loop {
+            let mut request = agent.get(self.url.as_str());
+
+            if let Some(cached) = &cached {
+                if let Some(etag) = &cached.etag {
+                    request = request.header("If-None-Match", etag);
+                }
+
+                if let Some(last_modified) = &cached.last_modified {
+                    request = request.header("If-Modified-Since", last_modified);
+                }
+            }
+
+            let mut response = request
+                .call()
+                .context(format!("failed to fetch source at {} over HTTP", self.url))?;
+
+            let status = response.status();
+
+            if status.as_u16() == 304
+                && let Some(cached) = cached
+            {
+                return Ok(cached.body);
+            }
+
+            if status.is_success() {
+                let etag = header(&response, "etag");
+                let last_modified = header(&response, "last-modified");
+                let body = response
+                    .body_mut()
+                    .read_to_string()
+                    .context("failed to read HTTP response to string")
+                    .context(format!("failed to fetch source at {} over HTTP", self.url))?;
+
+                if let Some(cache) = &self.cache
+                    && let Err(e) = cache.put(&CachedResponse {
+                        url: self.url.to_owned(),
+                        fetched_at: Utc::now(),
+                        etag,
+                        last_modified,
+                        body: body.to_owned(),
+                    })
+                {
+                    eprintln!("failed to cache response for {}: {e:#}", self.url);
+                }
+
+                return Ok(body);
+            }
+
+            if Self::is_retryable(&response) && attempt < self.config.max_retries {
+                sleep(Self::retry_delay(&response, attempt));
+                attempt += 1;
+                continue;
+            }
+
+            return Err(HttpStatusError {
+                url: self.url.to_owned(),
+                final_url: response.get_uri().to_string(),
+                status: status.as_u16(),
+                attempts: attempt + 1,
+            }
+            .into());
+        }
     }
 }
 
@@ -102,3 +289,347 @@ impl Fetch for LocalFileSource {
         read_to_string(&path).context(format!("failed to read file at {}", path.to_string_lossy()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::mpsc::{self, Receiver},
+        thread,
+    };
+
+    use super::*;
+
+    fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}\r\n"))
+            .collect::<String>();
+
+        format!(
+            "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// A stand-in server on localhost answering each connection with the next of the given
+    /// responses, along with the head of each request it received.
+    fn serve(responses: Vec<String>) -> (Url, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                sender.send(read_head(&mut reader)).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, receiver)
+    }
+
+    fn read_head(reader: &mut impl BufRead) -> String {
+        let mut head = String::new();
+
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                return head;
+            }
+            head.push_str(&line);
+        }
+    }
+
+    fn source(url: &Url) -> HttpSource {
+        HttpSource {
+            url: url.to_owned(),
+            config: HttpConfig {
+                max_retries: 2,
+                allow_http: true,
+                ..HttpConfig::default()
+            },
+            cache: None,
+        }
+    }
+
+    fn with_retry_after(value: &str) -> Response<()> {
+        Response::builder()
+            .status(503)
+            .header("retry-after", value)
+            .body(())
+            .unwrap()
+    }
+
+    #[test]
+    fn retry_delay_prefers_retry_after_seconds() {
+        let delay = HttpSource::retry_delay(&with_retry_after("7"), 3);
+
+        assert_eq!(delay, Duration::from_secs(7));
+    }
+
+    #[test]
+    fn retry_delay_reads_retry_after_date() {
+        let date = (Utc::now() + chrono::Duration::seconds(30)).to_rfc2822();
+        let delay = HttpSource::retry_delay(&with_retry_after(&date), 0);
+
+        assert!(
+            (Duration::from_secs(28)..=Duration::from_secs(30)).contains(&delay),
+            "{delay:?}"
+        );
+    }
+
+    #[test]
+    fn retry_delay_treats_past_date_as_no_delay() {
+        let date = (Utc::now() - chrono::Duration::seconds(30)).to_rfc2822();
+        let delay = HttpSource::retry_delay(&with_retry_after(&date), 2);
+
+        assert_eq!(delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn retry_delay_backs_off_exponentially() {
+        let response = Response::builder().status(503).body(()).unwrap();
+        let delays = (0..4)
+            .map(|attempt| HttpSource::retry_delay(&response, attempt).as_secs())
+            .collect::<Vec<_>>();
+
+        assert_eq!(delays, vec![1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn retry_delay_falls_back_on_unparseable_retry_after() {
+        let delay = HttpSource::retry_delay(&with_retry_after("soon"), 1);
+
+        assert_eq!(delay, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        let response = Response::builder().status(503).body(()).unwrap();
+
+        assert_eq!(
+            HttpSource::retry_delay(&with_retry_after("3600"), 0),
+            Duration::from_secs(RETRY_MAX_DELAY_SECS)
+        );
+        assert_eq!(
+            HttpSource::retry_delay(&response, 40),
+            Duration::from_secs(RETRY_MAX_DELAY_SECS)
+        );
+    }
+
+    #[test]
+    fn only_rate_limits_and_server_errors_are_retryable() {
+        let retryable = |status: u16| {
+            HttpSource::is_retryable(&Response::builder().status(status).body(()).unwrap())
+        };
+
+        assert!(retryable(429));
+        assert!(retryable(500));
+        assert!(retryable(503));
+        assert!(!retryable(404));
+        assert!(!retryable(403));
+    }
+
+    #[test]
+    fn fetch_retries_until_success() {
+        let (url, requests) = serve(vec![
+            response("503 Service Unavailable", &[("Retry-After", "0")], ""),
+            response("429 Too Many Requests", &[("Retry-After", "0")], ""),
+            response("200 OK", &[], "<html>listing</html>"),
+        ]);
+
+        let body = source(&url).fetch().unwrap();
+
+        assert_eq!(body, "<html>listing</html>");
+        assert_eq!(requests.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn fetch_gives_up_after_max_retries() {
+        let (url, requests) = serve(vec![
+            response(
+                "503 Service Unavailable",
+                &[("Retry-After", "0")],
+                ""
+            );
+            3
+        ]);
+
+        let error = source(&url).fetch().unwrap_err();
+        let error = error.downcast_ref::<HttpStatusError>().unwrap();
+
+        assert_eq!(error.status, 503);
+        assert_eq!(error.attempts, 3);
+        assert_eq!(error.url, url);
+        assert_eq!(requests.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn fetch_does_not_retry_client_errors() {
+        let (url, requests) = serve(vec![response("404 Not Found", &[], "")]);
+
+        let error = source(&url).fetch().unwrap_err();
+        let error = error.downcast_ref::<HttpStatusError>().unwrap();
+
+        assert_eq!(error.status, 404);
+        assert_eq!(error.attempts, 1);
+        assert_eq!(requests.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn fetch_reports_final_url_after_redirect() {
+        let (url, _requests) = serve(vec![
+            response("302 Found", &[("Location", "/closed")], ""),
+            response("410 Gone", &[], ""),
+        ]);
+
+        let error = source(&url).fetch().unwrap_err();
+        let error = error.downcast_ref::<HttpStatusError>().unwrap();
+
+        assert_eq!(error.status, 410);
+        assert_eq!(error.url, url);
+        assert_eq!(error.final_url, url.join("closed").unwrap().to_string());
+        assert!(error.to_string().contains("/closed"));
+    }
+
+    #[test]
+    fn fetch_sends_configured_user_agent() {
+        let (url, requests) = serve(vec![response("200 OK", &[], "")]);
+
+        let mut source = source(&url);
+        source.config.user_agent = "ply-test/1.0".to_owned();
+        source.fetch().unwrap();
+
+        let request = requests.recv().unwrap().to_lowercase();
+        assert!(request.contains("user-agent: ply-test/1.0"), "{request}");
+    }
+
+    #[test]
+    fn http_requires_opt_in() {
+        let url = Url::parse("http://intranet.example/jobs/1").unwrap();
+
+        let error = Source::new(&url, &FetchOptions::default()).err().unwrap();
+        assert!(error.to_string().contains("refusing to fetch"), "{error}");
+
+        let options = FetchOptions {
+            http: HttpConfig {
+                allow_http: true,
+                ..HttpConfig::default()
+            },
+            cache: None,
+        };
+        assert!(matches!(Source::new(&url, &options), Ok(Source::Http(_))));
+    }
+
+    #[test]
+    fn https_and_file_need_no_opt_in() {
+        let options = FetchOptions::default();
+        let https = Url::parse("https://jobs.ashbyhq.com/acme/1").unwrap();
+        let file = Url::parse("file:///tmp/listing.html").unwrap();
+
+        assert!(matches!(Source::new(&https, &options), Ok(Source::Http(_))));
+        assert!(matches!(
+            Source::new(&file, &options),
+            Ok(Source::LocalFile(_))
+        ));
+    }
+
+    #[test]
+    fn no_proxy_matches_hosts_and_subdomains() {
+        let no_proxy = "localhost, .internal.example,Corp.Example";
+
+        assert!(is_no_proxy(no_proxy, "localhost"));
+        assert!(is_no_proxy(no_proxy, "internal.example"));
+        assert!(is_no_proxy(no_proxy, "jobs.internal.example"));
+        assert!(is_no_proxy(no_proxy, "corp.example"));
+        assert!(is_no_proxy(no_proxy, "JOBS.CORP.EXAMPLE"));
+        assert!(!is_no_proxy(no_proxy, "notcorp.example"));
+        assert!(!is_no_proxy(no_proxy, "example"));
+        assert!(!is_no_proxy(no_proxy, "jobs.ashbyhq.com"));
+    }
+
+    #[test]
+    fn no_proxy_wildcard_and_empty_entries() {
+        assert!(is_no_proxy("*", "jobs.ashbyhq.com"));
+        assert!(!is_no_proxy("", "jobs.ashbyhq.com"));
+        assert!(!is_no_proxy(" , ,", "jobs.ashbyhq.com"));
+    }
+
+    #[test]
+    fn fetch_goes_through_configured_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy = format!("http://{}", listener.local_addr().unwrap());
+
+        // A stand-in proxy that opens the tunnel and then answers the request sent through it
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let connect = read_head(&mut reader);
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                .unwrap();
+
+            let request = read_head(&mut reader);
+            stream
+                .write_all(response("200 OK", &[], "proxied").as_bytes())
+                .unwrap();
+
+            (connect, request)
+        });
+
+        let url = Url::parse("http://listings.invalid/jobs/1").unwrap();
+        let mut source = source(&url);
+        source.config.proxy = Some(proxy);
+
+        assert_eq!(source.fetch().unwrap(), "proxied");
+
+        let (connect, request) = server.join().unwrap();
+        assert!(
+            connect.starts_with("CONNECT listings.invalid:80 "),
+            "{connect}"
+        );
+        assert!(request.starts_with("GET /jobs/1 "), "{request}");
+    }
+
+    #[test]
+    fn parser_runs_end_to_end_against_local_server() {
+        let listing = r#"<html><head><script type="application/ld+json">
+            {
+                "title": "Software Engineer, Platform",
+                "hiringOrganization": { "name": "Acme" },
+                "baseSalary": {
+                    "value": { "unitText": "YEAR", "minValue": "150000", "maxValue": "200000" }
+                }
+            }
+        </script></head><body>Build things</body></html>"#;
+        let (url, _requests) = serve(vec![response("200 OK", &[], listing)]);
+        let url = url.join("acme/1").unwrap();
+
+        let options = FetchOptions {
+            http: HttpConfig {
+                allow_http: true,
+                ..HttpConfig::default()
+            },
+            cache: None,
+        };
+        let scraped = crate::scrape::ScrapedContent::from_url(
+            &url,
+            Some(crate::parse::Parser::Ashby),
+            &options,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(scraped.job.company, "Acme");
+        assert_eq!(scraped.job.title, "Software Engineer");
+        assert_eq!(scraped.job.team.as_deref(), Some("Platform"));
+        assert_eq!(scraped.job.listing_url, Some(url));
+        assert!(scraped.job.salary_range.is_some());
+    }
+}
@@ -0,0 +1,465 @@
+use std::{collections::BTreeMap, fs};
+
+use anyhow::{Context, Result, anyhow};
+use camino::Utf8Path as Path;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use clap::ValueEnum;
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    application::{self, Application, Stage, StageType},
+    document::Document,
+    export::Format,
+    job::{Job, SalaryRange},
+};
+
+pub mod notion;
+
+const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%m/%d/%Y", "%d/%m/%Y", "%B %d, %Y"];
+const DATETIME_FORMATS: [&str; 5] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%m/%d/%Y %H:%M",
+    "%m/%d/%Y %I:%M%p",
+    "%B %d, %Y %I:%M %p",
+];
+
+/// A row of imported data, keyed by column.
+pub type Row = BTreeMap<String, String>;
+
+/// The application fields that imported columns can be mapped onto.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+pub enum Field {
+    Company,
+    Title,
+    Team,
+    Cycle,
+    AppliedAt,
+    CurrentStage,
+    StageStartedAt,
+    /// A salary or salary range, e.g. "$120k - $150k"
+    Salary,
+    SalaryLower,
+    SalaryUpper,
+    ListingUrl,
+    SourceUrl,
+    Notes,
+}
+
+impl Field {
+    /// The column this field is read from unless mapped otherwise, matching `ply export`.
+    fn column(&self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().replace('-', "_"))
+            .unwrap_or_default()
+    }
+
+    /// Every column this field is read from unless mapped otherwise, in order of preference, i.e.
+    /// its own and the names it commonly goes by in spreadsheets, Notion and Airtable.
+    fn columns(&self) -> Vec<String> {
+        let aliases: &[&str] = match self {
+            Field::Title => &["role", "position", "job_title", "job"],
+            Field::AppliedAt => &["date_applied", "applied_on", "applied", "date"],
+            Field::CurrentStage => &["status", "stage"],
+            Field::Salary => &["compensation", "pay"],
+            Field::ListingUrl => &["url", "link", "job_url", "listing"],
+            Field::Notes => &["note"],
+            _ => &[],
+        };
+
+        [self.column()]
+            .into_iter()
+            .chain(aliases.iter().map(|alias| alias.to_string()))
+            .collect()
+    }
+}
+
+/// A column's name as a field's, e.g. "Applied At" as "applied_at".
+fn column_name(column: &str) -> String {
+    column.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+/// Parse a mapping of a status onto a stage, given as `STATUS=STAGE`.
+pub fn parse_status(s: &str) -> std::result::Result<(String, StageType), String> {
+    let (status, stage) = s
+        .rsplit_once('=')
+        .ok_or(format!("expected STATUS=STAGE but got '{s}'"))?;
+
+    let stage = parse_stage(stage).map_err(|e| e.to_string())?;
+
+    Ok((status.trim().to_owned(), stage))
+}
+
+/// Parse a mapping of a column onto a field, given as `COLUMN=FIELD`.
+pub fn parse_mapping(s: &str) -> std::result::Result<(String, Field), String> {
+    let (column, field) = s
+        .rsplit_once('=')
+        .ok_or(format!("expected COLUMN=FIELD but got '{s}'"))?;
+
+    let field = Field::from_str(&field.trim().replace('_', "-"), true)?;
+
+    Ok((column.trim().to_owned(), field))
+}
+
+/// How the columns of imported rows map onto an application's fields. Columns named after a field,
+/// like those exported by `ply export`, or one of its common aliases map onto it unless it was
+/// mapped explicitly. Statuses, e.g. "Phone Screen", can be mapped onto stages too.
+pub struct Mapping {
+    pub columns: Vec<(String, Field)>,
+    pub statuses: Vec<(String, StageType)>,
+}
+
+impl Mapping {
+    fn value<'a>(&self, row: &'a Row, field: Field) -> Option<&'a str> {
+        let mapped = self
+            .columns
+            .iter()
+            .filter(|(_, mapped)| *mapped == field)
+            .map(|(column, _)| column.as_str())
+            .collect::<Vec<_>>();
+
+        let values = if mapped.is_empty() {
+            field
+                .columns()
+                .iter()
+                .flat_map(|name| {
+                    row.iter()
+                        .filter(|(column, _)| {
+                            column_name(column) == *name
+                                && !self.columns.iter().any(|(mapped, _)| mapped == *column)
+                        })
+                        .map(|(_, value)| value)
+                })
+                .collect::<Vec<_>>()
+        } else {
+            mapped
+                .iter()
+                .filter_map(|column| row.get(*column))
+                .collect()
+        };
+
+        values
+            .into_iter()
+            .map(|value| value.trim())
+            .find(|value| !value.is_empty())
+    }
+
+    /// The stage a status is mapped onto, or the stage it names.
+    fn stage(&self, status: &str) -> Result<StageType> {
+        if let Some((_, stage)) = self
+            .statuses
+            .iter()
+            .find(|(mapped, _)| mapped.trim().eq_ignore_ascii_case(status))
+        {
+            return Ok(*stage);
+        }
+
+        parse_stage(status).map_err(|_| {
+            anyhow!("unknown stage '{status}', map it onto one with --status '{status}=STAGE'")
+        })
+    }
+
+    fn required<'a>(&self, row: &'a Row, field: Field) -> Result<&'a str> {
+        self.value(row, field).ok_or(anyhow!(
+            "missing a value for {}, map a column onto it with --map COLUMN={}",
+            field.column(),
+            field.column()
+        ))
+    }
+
+    /// The application described by a row, with the mapped notes as its content.
+    pub fn document(&self, row: &Row, cycle: Option<&str>) -> Result<Document<Application>> {
+        let company = self.required(row, Field::Company)?;
+        let title = self.required(row, Field::Title)?;
+
+        let applied_at = self
+            .value(row, Field::AppliedAt)
+            .map(parse_date)
+            .transpose()?
+            .unwrap_or(Utc::now());
+
+        let salary_range = match self.value(row, Field::Salary) {
+            Some(salary) => parse_salary(salary)?,
+            None => SalaryRange::try_from_maybe_bounds(
+                self.value(row, Field::SalaryLower)
+                    .map(parse_amount)
+                    .transpose()?,
+                self.value(row, Field::SalaryUpper)
+                    .map(parse_amount)
+                    .transpose()?,
+            )?,
+        };
+
+        let job = Job {
+            listing_url: self
+                .value(row, Field::ListingUrl)
+                .map(parse_url)
+                .transpose()?,
+            source_url: self
+                .value(row, Field::SourceUrl)
+                .map(parse_url)
+                .transpose()?,
+            company: company.to_owned(),
+            title: title.to_owned(),
+            team: self.value(row, Field::Team).map(|team| team.to_owned()),
+            salary_range,
+            extra: toml::Table::new(),
+        };
+
+        let cycle = self
+            .value(row, Field::Cycle)
+            .or(cycle)
+            .map(|cycle| cycle.to_owned());
+
+        let mut application = application::new(job, cycle);
+        application.applied_at = applied_at;
+        application.stages = vec![Stage {
+            start_time: applied_at,
+            deadline: None,
+            name: None,
+            stage_type: StageType::Applied,
+            extra: toml::Table::new(),
+        }];
+
+        if let Some(stage) = self.value(row, Field::CurrentStage) {
+            let stage_type = self.stage(stage)?;
+            let start_time = self
+                .value(row, Field::StageStartedAt)
+                .map(parse_date)
+                .transpose()?
+                .unwrap_or(applied_at);
+
+            if stage_type != StageType::Applied {
+                application.stages.push(Stage {
+                    start_time,
+                    deadline: None,
+                    name: None,
+                    stage_type,
+                    extra: toml::Table::new(),
+                });
+            }
+        }
+
+        let mut document = application.new_document();
+        document.content = self
+            .value(row, Field::Notes)
+            .map(|notes| format!("{notes}\n"));
+
+        Ok(document)
+    }
+}
+
+/// Whether two applications look like the same application, i.e. they're for the same listing or
+/// for the same job and were applied to on the same day.
+pub fn is_duplicate(a: &Application, b: &Application) -> bool {
+    if let (Some(a), Some(b)) = (&a.job.listing_url, &b.job.listing_url) {
+        return a == b;
+    }
+
+    let same = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+
+    same(&a.job.company, &b.job.company)
+        && same(&a.job.title, &b.job.title)
+        && same(
+            a.job.team.as_deref().unwrap_or_default(),
+            b.job.team.as_deref().unwrap_or_default(),
+        )
+        && a.applied_at.date_naive() == b.applied_at.date_naive()
+}
+
+/// Read every row of a CSV, JSON or JSON lines file, inferring its format from its extension if
+/// not given.
+pub fn read_rows(path: &Path, format: Option<Format>) -> Result<Vec<Row>> {
+    let format = match format {
+        Some(format) => format,
+        None => match path.extension() {
+            Some("csv") => Format::Csv,
+            Some("json") => Format::Json,
+            Some("jsonl") => Format::Jsonl,
+            _ => {
+                return Err(anyhow!(
+                    "failed to infer the format of {path}, pass it with --format"
+                ));
+            }
+        },
+    };
+
+    let content = fs::read_to_string(path).context(format!("failed to read {path}"))?;
+
+    match format {
+        Format::Csv => csv_table(&content)
+            .map(|(_, rows)| rows)
+            .context(format!("failed to parse CSV from {path}")),
+        Format::Json => {
+            let values: Vec<Value> = serde_json::from_str(&content)
+                .context(format!("failed to parse JSON array from {path}"))?;
+            values.into_iter().map(json_row).collect()
+        }
+        Format::Jsonl => content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line)
+                    .context(format!("failed to parse JSON line from {path}"))
+                    .and_then(json_row)
+            })
+            .collect(),
+    }
+}
+
+/// The headers, in order, and rows of a CSV file.
+pub fn csv_table(content: &str) -> Result<(Vec<String>, Vec<Row>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(content.trim_start_matches('\u{feff}').as_bytes());
+
+    let headers = reader
+        .headers()
+        .context("failed to read CSV headers")?
+        .clone();
+
+    let rows = reader
+        .records()
+        .map(|record| {
+            let record = record.context("failed to read CSV row")?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(header, value)| (header.to_owned(), value.to_owned()))
+                .collect())
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((
+        headers.iter().map(|header| header.to_owned()).collect(),
+        rows,
+    ))
+}
+
+fn json_row(value: Value) -> Result<Row> {
+    let Value::Object(object) = value else {
+        return Err(anyhow!("expected each row to be a JSON object"));
+    };
+
+    Ok(object
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            Value::Null => None,
+            Value::String(s) => Some((key, s)),
+            value => Some((key, value.to_string())),
+        })
+        .collect())
+}
+
+/// Parse a timestamp or a date, taken to be at midnight UTC. Of a range like Notion's
+/// "March 4, 2026 → March 6, 2026", only its start is parsed.
+pub fn parse_date(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.split('→').next().unwrap_or(s).trim();
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(s) {
+        return Ok(datetime.to_utc());
+    }
+
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(s, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .map(|datetime| datetime.and_utc())
+        .ok_or(anyhow!("failed to parse date '{s}'"))
+}
+
+/// Parse a stage like "Team Matching" or "team_matching".
+pub fn parse_stage(s: &str) -> Result<StageType> {
+    StageType::from_str(&s.trim().replace([' ', '_'], "-"), true)
+        .map_err(|_| anyhow!("unknown stage '{s}'"))
+}
+
+fn parse_url(s: &str) -> Result<Url> {
+    Url::parse(s).context(format!("failed to parse URL '{s}'"))
+}
+
+/// Parse an amount like "120000", "$120,000" or "120k".
+fn parse_amount(s: &str) -> Result<u32> {
+    let amount = s.trim().trim_start_matches('$').replace(',', "");
+
+    let (amount, multiplier) = match amount.strip_suffix(['k', 'K']) {
+        Some(amount) => (amount, 1000.0),
+        None => (amount.as_str(), 1.0),
+    };
+
+    amount
+        .trim()
+        .parse::<f64>()
+        .map(|amount| (amount * multiplier).round() as u32)
+        .context(format!("failed to parse salary '{s}'"))
+}
+
+/// Parse a salary or a range of salaries like "$120k - $150k".
+fn parse_salary(s: &str) -> Result<Option<SalaryRange>> {
+    match s.split_once(['-', '–']) {
+        Some((lower, upper)) => {
+            SalaryRange::from_bounds(parse_amount(lower)?, parse_amount(upper)?).map(Some)
+        }
+        None => Ok(Some(SalaryRange::amount(parse_amount(s)?))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+    use crate::export::{self, ApplicationRow};
+
+    #[test]
+    fn exported_stages_import_again() {
+        let applied_at = Utc::now() - TimeDelta::days(7);
+
+        for stage_type in StageType::value_variants() {
+            let job = Job {
+                listing_url: None,
+                source_url: None,
+                company: "Acme".to_owned(),
+                title: "Engineer".to_owned(),
+                team: None,
+                salary_range: None,
+                extra: toml::Table::new(),
+            };
+            let mut application = application::new(job, None);
+            application.applied_at = applied_at;
+            application.stages.push(Stage {
+                start_time: Utc::now(),
+                deadline: None,
+                name: None,
+                stage_type: *stage_type,
+                extra: toml::Table::new(),
+            });
+
+            let mut csv = vec![];
+            let row = ApplicationRow::new(Path::new("acme.md"), &application);
+            export::rows(&mut csv, &[row], Format::Csv).unwrap();
+
+            let (_, rows) = csv_table(&String::from_utf8(csv).unwrap()).unwrap();
+            let mapping = Mapping {
+                columns: vec![],
+                statuses: vec![],
+            };
+            let imported = mapping.document(&rows[0], None).unwrap();
+
+            assert_eq!(
+                imported
+                    .record
+                    .current_stage()
+                    .map(|stage| stage.stage_type),
+                Some(*stage_type),
+                "{stage_type}"
+            );
+        }
+    }
+}
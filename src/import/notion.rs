@@ -0,0 +1,250 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{Cursor, Read},
+};
+
+use anyhow::{Context, Result, anyhow};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+use zip::ZipArchive;
+
+use crate::{
+    application::Application,
+    document::Document,
+    import::{Mapping, Row, csv_table},
+};
+
+const ALL_SUFFIX: &str = "_all";
+
+/// A page of a Notion database as exported to Markdown, i.e. a heading with its title followed by
+/// its properties as `Key: value` lines and then its body.
+struct Page {
+    title: String,
+    properties: BTreeMap<String, String>,
+    body: String,
+}
+
+impl Page {
+    fn parse(path: &Path, content: &str, headers: &BTreeSet<&str>) -> Self {
+        let mut lines = content.trim_start_matches('\u{feff}').lines().peekable();
+
+        let title = match lines.next_if(|line| line.starts_with("# ")) {
+            Some(heading) => heading.trim_start_matches("# ").trim().to_owned(),
+            None => strip_id(path.file_stem().unwrap_or_default()).to_owned(),
+        };
+
+        while lines.next_if(|line| line.trim().is_empty()).is_some() {}
+
+        let mut properties = BTreeMap::new();
+        while let Some((key, value)) = lines
+            .peek()
+            .and_then(|line| line.split_once(": "))
+            .filter(|(key, _)| headers.contains(key))
+        {
+            properties.insert(key.to_owned(), value.trim().to_owned());
+            lines.next();
+        }
+
+        let body = lines.collect::<Vec<_>>().join("\n");
+        let body = body.trim();
+
+        Page {
+            title,
+            properties,
+            body: if body.is_empty() {
+                String::new()
+            } else {
+                format!("{body}\n")
+            },
+        }
+    }
+
+    /// Whether this is the page for a row, i.e. it has the row's title and its properties agree.
+    fn is_for(&self, title: &str, row: &Row) -> bool {
+        self.title == title
+            && self.properties.iter().all(|(key, value)| {
+                row.get(key)
+                    .is_none_or(|row_value| row_value.trim() == value)
+            })
+    }
+}
+
+/// A filename without the ID Notion appends to it, e.g. "Acme 1f2e...9a" as "Acme".
+fn strip_id(name: &str) -> &str {
+    match name.rsplit_once(' ') {
+        Some((name, id)) if id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()) => name,
+        _ => name,
+    }
+}
+
+/// Every CSV and Markdown file of an export, keyed by its path within the export. Large exports
+/// are split into zips within the zip, so those are read too.
+fn files(path: &Path) -> Result<BTreeMap<PathBuf, String>> {
+    let mut files = BTreeMap::new();
+
+    if path.is_dir() {
+        read_dir(path, path, &mut files)?;
+    } else {
+        let bytes = fs::read(path).context(format!("failed to read Notion export {path}"))?;
+        read_zip(bytes, &mut files).context(format!("failed to read Notion export {path}"))?;
+    }
+
+    Ok(files)
+}
+
+fn read_dir(root: &Path, dir: &Path, files: &mut BTreeMap<PathBuf, String>) -> Result<()> {
+    let entries = dir
+        .read_dir_utf8()
+        .context(format!("failed to read directory {dir}"))?;
+
+    for entry in entries {
+        let path = entry
+            .context(format!("failed to read directory {dir}"))?
+            .into_path();
+
+        if path.is_dir() {
+            read_dir(root, &path, files)?;
+            continue;
+        }
+
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_owned();
+        match path.extension() {
+            Some("csv" | "md") => {
+                let content =
+                    fs::read_to_string(&path).context(format!("failed to read {path}"))?;
+                files.insert(relative, content);
+            }
+            Some("zip") => {
+                let bytes = fs::read(&path).context(format!("failed to read {path}"))?;
+                read_zip(bytes, files).context(format!("failed to read {path}"))?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn read_zip(bytes: Vec<u8>, files: &mut BTreeMap<PathBuf, String>) -> Result<()> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).context("failed to open zip")?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).context("failed to read zip entry")?;
+        let Some(path) = file
+            .enclosed_name()
+            .and_then(|path| PathBuf::from_path_buf(path).ok())
+        else {
+            continue;
+        };
+
+        if file.is_dir() {
+            continue;
+        }
+
+        match path.extension() {
+            Some("csv" | "md") => {
+                let mut content = String::new();
+                file.read_to_string(&mut content)
+                    .context(format!("failed to read {path} from zip"))?;
+                files.insert(path, content);
+            }
+            Some("zip") => {
+                let mut bytes = vec![];
+                file.read_to_end(&mut bytes)
+                    .context(format!("failed to read {path} from zip"))?;
+                read_zip(bytes, files).context(format!("failed to read {path} from zip"))?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// The CSVs of the exported databases along with the directories of their pages. Notion exports
+/// both the database's current view and, suffixed with `_all`, every row of it, so the latter is
+/// preferred. Databases nested within pages are ignored.
+fn databases(files: &BTreeMap<PathBuf, String>) -> Vec<(&PathBuf, PathBuf)> {
+    let mut databases = BTreeMap::new();
+
+    for path in files.keys().filter(|path| path.extension() == Some("csv")) {
+        let stem = path.file_stem().unwrap_or_default();
+        let is_all = stem.ends_with(ALL_SUFFIX);
+        let pages = path.with_file_name(stem.trim_end_matches(ALL_SUFFIX));
+
+        if is_all || !databases.contains_key(&pages) {
+            databases.insert(pages, path);
+        }
+    }
+
+    let depth = databases
+        .keys()
+        .map(|pages| pages.components().count())
+        .min()
+        .unwrap_or_default();
+
+    databases
+        .into_iter()
+        .filter(|(pages, _)| pages.components().count() == depth)
+        .map(|(pages, path)| (path, pages))
+        .collect()
+}
+
+/// An application for every row of the databases in a Notion export, either a zip or the directory
+/// it was extracted to, with the row's page as its notes.
+pub fn documents(
+    path: &Path,
+    mapping: &Mapping,
+    cycle: Option<&str>,
+) -> Result<Vec<Document<Application>>> {
+    let files = files(path)?;
+    let databases = databases(&files);
+
+    if databases.is_empty() {
+        return Err(anyhow!(
+            "failed to find a database in Notion export {path}, expected a CSV"
+        ));
+    }
+
+    let mut documents = vec![];
+
+    for (database, pages_dir) in databases {
+        let (headers, rows) =
+            csv_table(&files[database]).context(format!("failed to parse database {database}"))?;
+        let header_set = headers.iter().map(|header| header.as_str()).collect();
+
+        let mut pages = files
+            .iter()
+            .filter(|(path, _)| {
+                path.extension() == Some("md") && path.parent() == Some(pages_dir.as_path())
+            })
+            .map(|(path, content)| Page::parse(path, content, &header_set))
+            .collect::<Vec<_>>();
+
+        for (i, row) in rows.iter().enumerate() {
+            let mut document = mapping
+                .document(row, cycle)
+                .context(format!("failed to import row {} of {database}", i + 1))?;
+
+            let title = headers
+                .first()
+                .and_then(|header| row.get(header))
+                .map(|title| title.trim())
+                .unwrap_or_default();
+
+            if let Some(page) = pages
+                .iter()
+                .position(|page| page.is_for(title, row))
+                .map(|i| pages.remove(i))
+                && !page.body.is_empty()
+            {
+                document.content = Some(page.body);
+            }
+
+            documents.push(document);
+        }
+    }
+
+    Ok(documents)
+}
@@ -0,0 +1,196 @@
+use std::{collections::BTreeMap, fs, time::SystemTime};
+
+use anyhow::{Context, Error, Result};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    application::Application,
+    data::{document_paths, write_atomic},
+    document::{self, FrontmatterError},
+};
+
+const INDEX_FILE: &str = ".index.json";
+
+/// Bumped whenever the shape of the index changes so that stale indexes are rebuilt.
+const INDEX_VERSION: u32 = 2;
+
+/// A cache of every application's record, keyed by path, so that commands which only need records
+/// don't have to read and parse every document. Entries are revalidated against their document's
+/// modification time and size, so documents edited outside of ply are picked up.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Index {
+    version: u32,
+    entries: BTreeMap<PathBuf, IndexEntry>,
+
+    /// Whether anything changed since the index was loaded, i.e. whether it needs saving
+    #[serde(skip)]
+    changed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IndexEntry {
+    pub modified: SystemTime,
+    size: u64,
+
+    /// The document's record, or why it couldn't be read
+    pub record: std::result::Result<Application, String>,
+}
+
+impl IndexEntry {
+    fn read(path: &Path) -> Result<Self> {
+        let metadata =
+            fs::metadata(path).context(format!("failed to read metadata of document {path}"))?;
+
+        let record = document::read::<Application>(path)
+            .map(|document| document.record)
+            .map_err(|e| match e.downcast_ref::<FrontmatterError>() {
+                Some(e) => e.to_string(),
+                None => format!("{e:#}"),
+            });
+
+        Ok(IndexEntry {
+            modified: metadata
+                .modified()
+                .context(format!("failed to read modification time of {path}"))?,
+            size: metadata.len(),
+            record,
+        })
+    }
+
+    fn is_fresh(&self, path: &Path) -> bool {
+        fs::metadata(path).is_ok_and(|metadata| {
+            metadata.len() == self.size && metadata.modified().ok() == Some(self.modified)
+        })
+    }
+}
+
+impl Index {
+    fn path(data_dir: &Path) -> PathBuf {
+        data_dir.join(INDEX_FILE)
+    }
+
+    /// The index as last saved, or an empty one if there is none or it's unreadable or outdated.
+    pub fn load(data_dir: &Path) -> Self {
+        fs::read_to_string(Self::path(data_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Index>(&content).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or(Index {
+                version: INDEX_VERSION,
+                entries: BTreeMap::new(),
+                changed: false,
+            })
+    }
+
+    /// Save the index if it changed since it was loaded.
+    pub fn save(&mut self, data_dir: &Path) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+
+        let path = Self::path(data_dir);
+        let content = serde_json::to_vec(self).context("failed to serialize index")?;
+
+        write_atomic(&path, &content, false).context(format!("failed to write index at {path}"))?;
+        self.changed = false;
+
+        Ok(())
+    }
+
+    /// Bring the index up to date with the data directory, rereading only the documents that were
+    /// added or changed since they were indexed. Documents that couldn't be read at all, e.g.
+    /// because one was removed mid-scan, are returned rather than indexed so they're retried.
+    pub fn refresh(&mut self, data_dir: &Path) -> Result<Vec<(PathBuf, Error)>> {
+        let paths = document_paths(data_dir)?;
+
+        let stale = self
+            .entries
+            .keys()
+            .filter(|path| paths.binary_search(path).is_err())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for path in stale {
+            self.entries.remove(&path);
+            self.changed = true;
+        }
+
+        let mut failures = vec![];
+
+        for path in paths {
+            if self
+                .entries
+                .get(&path)
+                .is_some_and(|entry| entry.is_fresh(&path))
+            {
+                continue;
+            }
+
+            match IndexEntry::read(&path) {
+                Ok(entry) => {
+                    self.entries.insert(path, entry);
+                }
+                Err(e) => {
+                    self.entries.remove(&path);
+                    failures.push((path, e));
+                }
+            }
+            self.changed = true;
+        }
+
+        Ok(failures)
+    }
+
+    /// Record that a document was written, moved from `previous` if given, or deleted if its path
+    /// no longer exists.
+    pub fn update(&mut self, data_dir: &Path, path: &Path, previous: Option<&Path>) -> Result<()> {
+        if let Some(previous) = previous.and_then(|previous| Self::key(data_dir, previous)) {
+            self.entries.remove(&previous);
+            self.changed = true;
+        }
+
+        let Some(key) = Self::key(data_dir, path) else {
+            return Ok(());
+        };
+
+        if path.is_file() {
+            self.entries.insert(key, IndexEntry::read(path)?);
+        } else {
+            self.entries.remove(&key);
+        }
+        self.changed = true;
+
+        Ok(())
+    }
+
+    /// A path as it's keyed in the index, i.e. joined onto the data directory the way paths found
+    /// by scanning it are, or nothing if it's outside of the data directory.
+    fn key(data_dir: &Path, path: &Path) -> Option<PathBuf> {
+        if path.starts_with(data_dir) {
+            return Some(path.to_owned());
+        }
+
+        // The path may not exist anymore, so only its directory can be resolved
+        let dir = path.parent()?;
+        let dir = if dir.as_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+
+        let relative = dir
+            .canonicalize_utf8()
+            .ok()?
+            .strip_prefix(data_dir.canonicalize_utf8().ok()?)
+            .ok()?
+            .join(path.file_name()?);
+
+        Some(data_dir.join(relative))
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &IndexEntry)> {
+        self.entries.iter()
+    }
+}
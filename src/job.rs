@@ -1,8 +1,9 @@
 use anyhow::{Error, Result};
 use bon::Builder;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::data::id_filename;
+use crate::data::{id_hash, id_timestamp_filename};
 
 pub mod salary;
 pub use salary::SalaryRange;
@@ -10,14 +11,29 @@ pub use salary::SalaryRange;
 #[derive(Builder, Serialize, Deserialize, Clone)]
 pub struct Job {
     pub listing_url: Option<url::Url>,
+
+    /// The original posting on the company's job board when `listing_url` is an aggregator
+    pub source_url: Option<url::Url>,
+
     pub company: String,
     pub title: String,
     pub team: Option<String>,
     pub salary_range: Option<SalaryRange>,
+
+    /// Keys added to `[job]` by hand, e.g. the location or who referred you
+    #[serde(flatten)]
+    #[builder(default)]
+    pub extra: toml::Table,
 }
 
 impl Job {
-    pub fn filename(&self) -> Result<String> {
+    /// A short hash of the listing URL which prefixes this job's listing snapshot filenames
+    pub fn id(&self) -> Option<String> {
+        self.listing_url.as_ref().map(|url| id_hash(url.as_str()))
+    }
+
+    /// The filename of a version of this job's listing snapshot taken at the given time
+    pub fn snapshot_filename(&self, timestamp: &DateTime<Utc>) -> Result<String> {
         let url = self.listing_url.clone().ok_or_else(|| {
             Error::msg("cannot create unique filename for job without a listing URL")
         })?;
@@ -27,6 +43,6 @@ impl Job {
             attrs.push(team.to_owned());
         }
 
-        Ok(id_filename(url.as_str(), attrs))
+        Ok(id_timestamp_filename(url.as_str(), timestamp, attrs))
     }
 }
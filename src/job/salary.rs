@@ -1,14 +1,18 @@
 use anyhow::{Result, anyhow};
+use core::fmt;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct SalaryRange {
     pub lower: u32,
     pub range: Option<u32>,
+
+    /// Fields added by hand that ply doesn't know about, e.g. the currency
+    #[serde(flatten)]
+    pub extra: toml::Table,
 }
 
 impl SalaryRange {
-    #[allow(dead_code)]
     pub fn upper(&self) -> Option<u32> {
         self.range.map(|r| self.lower + r)
     }
@@ -23,6 +27,7 @@ impl SalaryRange {
         Ok(Self {
             lower,
             range: Some(upper.abs_diff(lower)),
+            extra: toml::Table::new(),
         })
     }
 
@@ -30,6 +35,7 @@ impl SalaryRange {
         Self {
             lower: amount,
             range: None,
+            extra: toml::Table::new(),
         }
     }
 
@@ -44,3 +50,12 @@ impl SalaryRange {
         })
     }
 }
+
+impl fmt::Display for SalaryRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.upper() {
+            Some(upper) => write!(f, "${}-${}", self.lower, upper),
+            None => write!(f, "${}", self.lower),
+        }
+    }
+}
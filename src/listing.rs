@@ -0,0 +1,147 @@
+use std::{
+    fs::{self, File},
+    io::Read,
+};
+
+use anyhow::{Context, Result};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+    data::parse_filename_timestamp,
+    document::{self, Split},
+    job::SalaryRange,
+    parse::Parser,
+};
+
+pub const LISTINGS_DIR: &str = "listings";
+
+const MARKDOWN_EXTENSION: &str = "md";
+const HTML_EXTENSION: &str = "html";
+const COMPRESSED_HTML_EXTENSION: &str = "html.gz";
+
+/// The frontmatter of a Markdown listing snapshot, recording where and how it was scraped.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SnapshotHeader {
+    pub listing_url: Option<Url>,
+    pub source_url: Option<Url>,
+    pub fetched_at: DateTime<Utc>,
+    pub parser: Parser,
+}
+
+/// One version of a listing snapshot, i.e. a Markdown file and possibly its raw HTML.
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+pub fn html_extension(compress: bool) -> &'static str {
+    if compress {
+        COMPRESSED_HTML_EXTENSION
+    } else {
+        HTML_EXTENSION
+    }
+}
+
+/// All versions of the snapshot for the job with the given ID, oldest first. Snapshots taken
+/// before versioning have no timestamp in their filename and sort first.
+pub fn snapshots(content_dir: &Path, id: &str) -> Result<Vec<Snapshot>> {
+    if !content_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let prefix = format!("{id}.");
+    let mut snapshots = vec![];
+
+    for entry in content_dir
+        .read_dir_utf8()
+        .context(format!("failed to read content directory {content_dir}"))?
+    {
+        let entry = entry.context(format!("failed to read entry in {content_dir}"))?;
+        let name = entry.file_name();
+
+        if name.starts_with(&prefix) && entry.path().extension() == Some(MARKDOWN_EXTENSION) {
+            let timestamp = name.split('.').nth(1).and_then(parse_filename_timestamp);
+
+            snapshots.push(Snapshot {
+                path: entry.path().to_owned(),
+                timestamp,
+            });
+        }
+    }
+
+    snapshots.sort_by_key(|snapshot| snapshot.timestamp);
+
+    Ok(snapshots)
+}
+
+/// The latest snapshot version that has raw HTML, along with the path to it.
+pub fn latest_html(content_dir: &Path, id: &str) -> Result<Option<(Snapshot, PathBuf)>> {
+    Ok(snapshots(content_dir, id)?
+        .into_iter()
+        .rev()
+        .find_map(|snapshot| snapshot.html_path().map(|path| (snapshot, path))))
+}
+
+pub fn read_html(path: &Path) -> Result<String> {
+    let f = File::open(path).context(format!("failed to open HTML snapshot at {path}"))?;
+    let mut content = String::new();
+
+    if path
+        .as_str()
+        .ends_with(&format!(".{COMPRESSED_HTML_EXTENSION}"))
+    {
+        GzDecoder::new(f).read_to_string(&mut content)
+    } else {
+        let mut f = f;
+        f.read_to_string(&mut content)
+    }
+    .context(format!("failed to read HTML snapshot at {path}"))?;
+
+    Ok(content)
+}
+
+impl Snapshot {
+    pub fn html_path(&self) -> Option<PathBuf> {
+        [COMPRESSED_HTML_EXTENSION, HTML_EXTENSION]
+            .into_iter()
+            .map(|extension| self.path.with_extension(extension))
+            .find(|path| path.is_file())
+    }
+
+    /// The snapshot's header, if it has one, and its Markdown description.
+    pub fn read(&self) -> Result<(Option<SnapshotHeader>, String)> {
+        let content = fs::read_to_string(&self.path)
+            .context(format!("failed to read listing snapshot at {}", self.path))?;
+
+        let Ok(Split {
+            frontmatter, body, ..
+        }) = document::split(&content)
+        else {
+            return Ok((None, content));
+        };
+
+        let header = toml::from_str(frontmatter).context(format!(
+            "failed to parse header of listing snapshot at {}",
+            self.path
+        ))?;
+
+        Ok((Some(header), body.to_owned()))
+    }
+
+    /// The salary range parsed from this version's raw HTML, if it has both HTML and a header
+    /// recording which parser to use.
+    pub fn salary_range(&self) -> Result<Option<SalaryRange>> {
+        let (Some(header), Some(html_path)) = (self.read()?.0, self.html_path()) else {
+            return Ok(None);
+        };
+
+        let role = header.parser.parse_role(&read_html(&html_path)?)?;
+
+        Ok(role.and_then(|role| role.salary_range))
+    }
+}
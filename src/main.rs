@@ -2,14 +2,24 @@ use crate::{command::Run, config::PlyConfig};
 use anyhow::Result;
 
 mod application;
+mod cache;
 mod command;
 mod config;
 mod data;
 mod document;
+mod editor;
+mod export;
+mod extract;
 mod fetch;
+mod import;
+mod index;
 mod job;
+mod listing;
+mod notes;
 mod parse;
+mod repository;
 mod scrape;
+mod template;
 
 fn main() -> Result<()> {
     let config = config::config();
@@ -0,0 +1,199 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    PlyConfig,
+    application::{Application, Stage},
+    document::Document,
+    template,
+};
+
+/// The heading of the section of an application's notes for one of its stages, e.g.
+/// "## Onsite (2025-10-03)", named after the stage if it has a name.
+pub fn stage_heading(stage: &Stage) -> String {
+    let title = stage
+        .name
+        .to_owned()
+        .unwrap_or_else(|| stage.stage_type.title());
+
+    format!("## {title} ({})", stage.start_time.format("%Y-%m-%d"))
+}
+
+fn is_section_heading(line: &str) -> bool {
+    line.starts_with("# ") || line.starts_with("## ")
+}
+
+/// Append an entry to the end of a section of the notes, adding the section to the end of the
+/// notes if it isn't there yet.
+pub fn append(content: &str, heading: &str, entry: &str) -> String {
+    let mut lines = content.lines().collect::<Vec<_>>();
+    let entry = entry.trim_matches('\n').lines();
+
+    match lines.iter().position(|line| line.trim_end() == heading) {
+        Some(start) => {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| is_section_heading(line))
+                .map(|i| start + 1 + i)
+                .unwrap_or(lines.len());
+
+            // Entries go after the section's last line, with a blank line on either side
+            let mut last = end;
+            while last > start + 1 && lines[last - 1].trim().is_empty() {
+                last -= 1;
+            }
+
+            let mut inserted = vec![""];
+            inserted.extend(entry);
+            if end < lines.len() {
+                inserted.push("");
+            }
+
+            lines.splice(last..end, inserted);
+        }
+        None => {
+            while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                lines.pop();
+            }
+
+            if !lines.is_empty() {
+                lines.push("");
+            }
+
+            lines.push(heading);
+            lines.push("");
+            lines.extend(entry);
+        }
+    }
+
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Append an entry to the notes of an application's document, keeping its line endings.
+pub fn append_to(document: &mut Document<Application>, heading: &str, entry: &str) {
+    let content = document
+        .content
+        .as_deref()
+        .unwrap_or_default()
+        .replace("\r\n", "\n");
+
+    let mut content = append(&content, heading, entry);
+    if document.crlf {
+        content = content.replace('\n', "\r\n");
+    }
+
+    document.content = Some(content);
+}
+
+/// Add the section for a stage to an application's notes from the template for its type, unless
+/// there's no template for it or the notes already have the section.
+pub fn add_stage_section(
+    config: &PlyConfig,
+    document: &mut Document<Application>,
+    stage: &Stage,
+) -> Result<()> {
+    let heading = stage_heading(stage);
+    let has_section = document
+        .content
+        .as_deref()
+        .is_some_and(|content| content.lines().any(|line| line.trim_end() == heading));
+
+    if has_section {
+        return Ok(());
+    }
+
+    if let Some(section) = template::stage_section(config, &document.record, stage)? {
+        append_to(document, &heading, &section);
+    }
+
+    Ok(())
+}
+
+/// Add a note, timestamped with when it was taken, under the application's current stage.
+pub fn add_note(document: &mut Document<Application>, note: &str, now: DateTime<Utc>) {
+    let heading = match document.record.current_stage() {
+        Some(stage) => stage_heading(&stage),
+        None => "## Notes".to_owned(),
+    };
+
+    let entry = format!("### {}\n\n{}", now.format("%Y-%m-%d %H:%M"), note.trim());
+
+    append_to(document, &heading, &entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{application, job::Job};
+
+    #[test]
+    fn appends_to_end_of_existing_section() {
+        let content =
+            "# Acme\n\n## Screen (2026-01-05)\n\nFirst call\n\n\n## Onsite (2026-01-12)\n\nPrep\n";
+
+        assert_eq!(
+            append(content, "## Screen (2026-01-05)", "Follow up"),
+            "# Acme\n\n## Screen (2026-01-05)\n\nFirst call\n\nFollow up\n\n## Onsite (2026-01-12)\n\nPrep\n"
+        );
+    }
+
+    #[test]
+    fn appends_to_existing_last_section() {
+        let content = "## Notes\n\nFirst\n\n";
+
+        assert_eq!(
+            append(content, "## Notes", "Second"),
+            "## Notes\n\nFirst\n\nSecond\n"
+        );
+    }
+
+    #[test]
+    fn adds_missing_section_at_end() {
+        let content = "# Acme\n\nSome notes\n\n\n";
+
+        assert_eq!(
+            append(content, "## Notes", "### 2026-01-05 10:00\n\nA note"),
+            "# Acme\n\nSome notes\n\n## Notes\n\n### 2026-01-05 10:00\n\nA note\n"
+        );
+    }
+
+    #[test]
+    fn adds_section_to_empty_notes() {
+        assert_eq!(append("", "## Notes", "A note"), "## Notes\n\nA note\n");
+    }
+
+    #[test]
+    fn handles_missing_trailing_newline() {
+        assert_eq!(
+            append("## Notes\n\nFirst", "## Notes", "Second"),
+            "## Notes\n\nFirst\n\nSecond\n"
+        );
+        assert_eq!(
+            append("Some notes", "## Notes", "A note"),
+            "Some notes\n\n## Notes\n\nA note\n"
+        );
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let job = Job {
+            listing_url: None,
+            source_url: None,
+            company: "Acme".to_owned(),
+            title: "Engineer".to_owned(),
+            team: None,
+            salary_range: None,
+            extra: toml::Table::new(),
+        };
+        let mut document = application::new(job, None).new_document();
+        document.crlf = true;
+        document.content = Some("## Notes\r\n\r\nFirst\r\n\r\n## Later\r\n".to_owned());
+
+        append_to(&mut document, "## Notes", "Second\nline");
+
+        assert_eq!(
+            document.content.unwrap(),
+            "## Notes\r\n\r\nFirst\r\n\r\nSecond\r\nline\r\n\r\n## Later\r\n"
+        );
+    }
+}
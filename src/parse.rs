@@ -1,4 +1,5 @@
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
@@ -39,10 +40,14 @@ pub struct Role {
     pub title: String,
     pub team: Option<String>,
     pub salary_range: Option<SalaryRange>,
+
+    /// The URL of the original posting when the parsed page is from an aggregator
+    pub source_url: Option<Url>,
 }
 
 #[allow(dead_code)]
-#[derive(Default, Clone, clap::ValueEnum)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Parser {
     Apple,
     Ashby,
@@ -59,7 +64,7 @@ pub enum Parser {
 
 impl Parser {
     pub fn infer(url: &Url) -> Option<Self> {
-        if url.scheme() != "https" {
+        if !matches!(url.scheme(), "https" | "http") {
             return None;
         }
 
@@ -96,4 +101,15 @@ impl Parser {
             Parser::Unimplemented => Ok(None),
         }
     }
+
+    /// The HTML of just the job description, for parsers that know where it lives in the page
+    pub fn parse_description(&self, s: &str) -> Result<Option<String>> {
+        match self {
+            Parser::Ashby => Ashby::parse_description(s),
+            Parser::Greenhouse => Greenhouse::parse_description(s),
+            Parser::HiringCafe => HiringCafe::parse_description(s),
+            Parser::Netflix => Netflix::parse_description(s),
+            _ => Ok(None),
+        }
+    }
 }
@@ -55,6 +55,7 @@ impl Parse<&str, Role> for Apple {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range: None,
+            source_url: None,
         }))
     }
 }
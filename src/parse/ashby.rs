@@ -66,8 +66,8 @@ impl Ashby {
     }
 }
 
-impl Parse<&str, Role> for Ashby {
-    fn parse(&self, s: &str) -> Result<Option<Role>> {
+impl Ashby {
+    fn parse_job_posting_data(s: &str) -> Result<Value> {
         let document = Html::parse_document(s);
         let job_posting_data_selector =
             Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
@@ -80,8 +80,20 @@ impl Parse<&str, Role> for Ashby {
             .collect::<Vec<_>>()
             .join("");
 
-        let job_posting_data: Value = serde_json::from_str(&job_posting_data)
-            .context("failed to parse job posting data as JSON")?;
+        serde_json::from_str(&job_posting_data).context("failed to parse job posting data as JSON")
+    }
+
+    pub fn parse_description(s: &str) -> Result<Option<String>> {
+        let job_posting_data = Self::parse_job_posting_data(s)?;
+        Ok(job_posting_data["description"]
+            .as_str()
+            .map(|d| d.to_owned()))
+    }
+}
+
+impl Parse<&str, Role> for Ashby {
+    fn parse(&self, s: &str) -> Result<Option<Role>> {
+        let job_posting_data = Self::parse_job_posting_data(s)?;
 
         let company = Self::parse_company(&job_posting_data)?;
         let (title, team) = Self::parse_title_and_team(&job_posting_data)?;
@@ -92,6 +104,7 @@ impl Parse<&str, Role> for Ashby {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url: None,
         }))
     }
 }
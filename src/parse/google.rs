@@ -55,6 +55,7 @@ impl Parse<&str, Role> for Google {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url: None,
         }))
     }
 }
@@ -79,6 +79,18 @@ impl Greenhouse {
     }
 }
 
+impl Greenhouse {
+    pub fn parse_description(s: &str) -> Result<Option<String>> {
+        let document = Html::parse_document(s);
+        let description_selector = Selector::parse(".job__description").unwrap();
+
+        Ok(document
+            .select(&description_selector)
+            .next()
+            .map(|description| description.inner_html()))
+    }
+}
+
 impl Parse<&str, Role> for Greenhouse {
     fn parse(&self, s: &str) -> Result<Option<Role>> {
         let document = Html::parse_document(s);
@@ -91,6 +103,7 @@ impl Parse<&str, Role> for Greenhouse {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url: None,
         }))
     }
 }
@@ -1,7 +1,9 @@
 use std::ops::Not;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Error, Result};
 use scraper::{Html, Selector};
+use serde_json::Value;
+use url::Url;
 
 use crate::{
     job::SalaryRange,
@@ -55,6 +57,50 @@ impl HiringCafe {
 
         SalaryRange::parse(&salary)
     }
+
+    fn parse_next_data(document: &Html) -> Result<Value> {
+        let next_data_selector = Selector::parse("script#__NEXT_DATA__").unwrap();
+        let next_data = document
+            .select(&next_data_selector)
+            .next()
+            .context("failed to select page data from document")?
+            .text()
+            .collect::<Vec<_>>()
+            .join("");
+
+        serde_json::from_str(&next_data).context("failed to parse page data as JSON")
+    }
+
+    pub fn parse_description(s: &str) -> Result<Option<String>> {
+        let next_data = Self::parse_next_data(&Html::parse_document(s))?;
+        Ok(
+            next_data["props"]["pageProps"]["job"]["job_information"]["description"]
+                .as_str()
+                .map(|d| d.to_owned()),
+        )
+    }
+
+    fn parse_source_url(document: &Html) -> Result<Option<Url>> {
+        let next_data = Self::parse_next_data(document)?;
+
+        let apply_url = match next_data["props"]["pageProps"]["job"]["apply_url"].as_str() {
+            Some(apply_url) => apply_url,
+            None => return Ok(None),
+        };
+
+        let mut apply_url = Url::parse(apply_url)
+            .map_err(|e| Error::new(e).context(format!("failed to parse apply URL {apply_url}")))?;
+
+        // Ashby links straight to the application form, but the listing itself lives one level up
+        if apply_url.domain() == Some("jobs.ashbyhq.com")
+            && let Some(listing_path) = apply_url.path().strip_suffix("/application")
+        {
+            let listing_path = listing_path.to_owned();
+            apply_url.set_path(&listing_path);
+        }
+
+        Ok(Some(apply_url))
+    }
 }
 
 impl Parse<&str, Role> for HiringCafe {
@@ -64,11 +110,86 @@ impl Parse<&str, Role> for HiringCafe {
         let (title, team) = Self::parse_title_and_team(&document)?;
         let salary_range = Self::parse_salary_range(&document)?;
 
+        // The listing is still worth having without the source, but not without saying so
+        let source_url = Self::parse_source_url(&document).unwrap_or_else(|e| {
+            eprintln!(
+                "warning: failed to find the source listing, using only hiring.cafe's: {e:#}"
+            );
+            None
+        });
+
         Ok(Some(Role {
             company: company.to_owned(),
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url,
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_apply_url(apply_url: &str) -> Html {
+        Html::parse_document(&format!(
+            r#"<script id="__NEXT_DATA__" type="application/json">
+                {{"props": {{"pageProps": {{"job": {{"apply_url": "{apply_url}"}}}}}}}}
+            </script>"#
+        ))
+    }
+
+    #[test]
+    fn parses_example_listing_with_source() {
+        let listing = include_str!("../../example_listings/hiringcafe.html");
+        let role = HiringCafe {}.parse(listing).unwrap().unwrap();
+
+        assert_eq!(
+            role.source_url.unwrap().as_str(),
+            "https://jobs.ashbyhq.com/openai/c6c4c2fb-cb2c-45d4-9f7c-e3356cd534d7"
+        );
+    }
+
+    #[test]
+    fn strips_ashby_application_suffix() {
+        let document =
+            with_apply_url("https://jobs.ashbyhq.com/acme/0d5e6f7a-1b2c/application?src=cafe");
+        let source_url = HiringCafe::parse_source_url(&document).unwrap().unwrap();
+
+        assert_eq!(
+            source_url.as_str(),
+            "https://jobs.ashbyhq.com/acme/0d5e6f7a-1b2c?src=cafe"
+        );
+    }
+
+    #[test]
+    fn keeps_other_application_links() {
+        let document = with_apply_url("https://boards.example.com/acme/1/application");
+        let source_url = HiringCafe::parse_source_url(&document).unwrap().unwrap();
+
+        assert_eq!(
+            source_url.as_str(),
+            "https://boards.example.com/acme/1/application"
+        );
+    }
+
+    #[test]
+    fn reports_bad_page_data() {
+        assert!(HiringCafe::parse_source_url(&with_apply_url("not a url")).is_err());
+
+        let document = Html::parse_document(
+            r#"<script id="__NEXT_DATA__" type="application/json">{ truncated</script>"#,
+        );
+        assert!(HiringCafe::parse_source_url(&document).is_err());
+    }
+
+    #[test]
+    fn no_apply_link_is_no_source() {
+        let document = Html::parse_document(
+            r#"<script id="__NEXT_DATA__" type="application/json">{"props": {}}</script>"#,
+        );
+
+        assert!(HiringCafe::parse_source_url(&document).unwrap().is_none());
+    }
+}
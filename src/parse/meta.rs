@@ -57,6 +57,7 @@ impl Parse<&str, Role> for Meta {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url: None,
         }))
     }
 }
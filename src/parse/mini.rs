@@ -80,6 +80,7 @@ impl Parse<&str, Role> for Mini {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url: None,
         }))
     }
 }
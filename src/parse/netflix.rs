@@ -53,8 +53,8 @@ impl Netflix {
     }
 }
 
-impl Parse<&str, Role> for Netflix {
-    fn parse(&self, s: &str) -> Result<Option<Role>> {
+impl Netflix {
+    fn parse_job_posting_data(s: &str) -> Result<Value> {
         let job_posting_data_selector =
             Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
         let document = Html::parse_document(s);
@@ -66,8 +66,20 @@ impl Parse<&str, Role> for Netflix {
             .collect::<Vec<_>>()
             .join("");
 
-        let job_posting_data: Value = serde_json::from_str(&job_posting_data)
-            .context("failed to parse job posting data as JSON")?;
+        serde_json::from_str(&job_posting_data).context("failed to parse job posting data as JSON")
+    }
+
+    pub fn parse_description(s: &str) -> Result<Option<String>> {
+        let job_posting_data = Self::parse_job_posting_data(s)?;
+        Ok(job_posting_data["description"]
+            .as_str()
+            .map(|d| html_escape::decode_html_entities(d).into_owned()))
+    }
+}
+
+impl Parse<&str, Role> for Netflix {
+    fn parse(&self, s: &str) -> Result<Option<Role>> {
+        let job_posting_data = Self::parse_job_posting_data(s)?;
 
         let company = Self::parse_company(&job_posting_data)?;
         let (title, team) = Self::parse_title_and_team(&job_posting_data)?;
@@ -78,6 +90,7 @@ impl Parse<&str, Role> for Netflix {
             title: title.to_owned(),
             team: team.to_owned(),
             salary_range,
+            source_url: None,
         }))
     }
 }
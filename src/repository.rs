@@ -0,0 +1,267 @@
+use std::{cell::RefCell, fs, time::SystemTime};
+
+use anyhow::{Context, Error, Result, anyhow};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+
+use crate::{
+    application::Application,
+    config::PlyConfig,
+    data::{remove_empty_parents, resolve_document},
+    document::{self, Document, ReadAll, read_all, skip_failures},
+    index::Index,
+};
+
+/// Where applications are kept. Commands find, create, update, move and delete applications
+/// through a store rather than touching their documents directly, so that how and where they're
+/// kept can change without the commands needing to.
+pub trait Store {
+    /// Every application, along with the documents that couldn't be read.
+    fn all(&self) -> Result<ReadAll<Application>>;
+
+    /// Every application's record without its notes, which is cheaper than reading every
+    /// application in full when that's all that's needed.
+    fn records(&self) -> Result<Records>;
+
+    /// When each application was last changed, which is enough to notice applications being
+    /// added, removed or changed without reading them.
+    fn modification_times(&self) -> Result<Vec<(PathBuf, SystemTime)>>;
+
+    /// The application at the given path, or with the given ID i.e. a unique prefix of its filename.
+    fn find(&self, id_or_path: &str) -> Result<Document<Application>>;
+
+    /// The applications with the given IDs or paths, or every application if `all` is set, in
+    /// which case those that can't be read are skipped with a warning.
+    fn find_all(&self, ids_or_paths: &[String], all: bool) -> Result<Vec<Document<Application>>> {
+        if all {
+            return Ok(self
+                .all()?
+                .into_documents(false)?
+                .into_iter()
+                .map(|(_, document)| document)
+                .collect());
+        }
+
+        ids_or_paths
+            .iter()
+            .map(|id_or_path| self.find(id_or_path))
+            .collect()
+    }
+
+    /// Store a new application along with its notes, returning where it was stored.
+    fn create(&self, document: &Document<Application>) -> Result<PathBuf>;
+
+    /// Save changes to an application, returning its new path if it moved as a result.
+    fn update(&self, document: &mut Document<Application>) -> Result<Option<PathBuf>>;
+
+    /// Rename an application to match its company, title and team, returning its new path if it
+    /// was renamed.
+    fn rename(&self, document: &mut Document<Application>) -> Result<Option<PathBuf>>;
+
+    /// Move an application into the archive, returning its new path if it was moved.
+    fn archive(&self, document: &mut Document<Application>) -> Result<Option<PathBuf>>;
+
+    fn is_archived(&self, path: &Path) -> bool;
+
+    fn delete(&self, document: Document<Application>) -> Result<()>;
+}
+
+/// Every application's record, split into those that could be read and those that couldn't.
+pub struct Records {
+    pub records: Vec<(PathBuf, Application)>,
+    pub failures: Vec<(PathBuf, Error)>,
+}
+
+impl Records {
+    /// The records that could be read, with the rest handled by [`skip_failures`].
+    pub fn into_records(self, strict: bool) -> Result<Vec<(PathBuf, Application)>> {
+        skip_failures(self.failures, strict)?;
+
+        Ok(self.records)
+    }
+}
+
+/// Applications kept as Markdown documents within the data directory, with an index of their
+/// records kept up to date as they're written.
+pub struct Repository<'a> {
+    config: &'a PlyConfig,
+
+    /// The index, loaded when first needed and kept up to date in memory, then saved once when the
+    /// repository is dropped rather than after every write
+    index: RefCell<Option<Index>>,
+}
+
+impl<'a> Repository<'a> {
+    pub fn new(config: &'a PlyConfig) -> Self {
+        Repository {
+            config,
+            index: RefCell::new(None),
+        }
+    }
+
+    fn with_index<T>(&self, f: impl FnOnce(&mut Index) -> T) -> T {
+        let mut index = self.index.borrow_mut();
+        f(index.get_or_insert_with(|| Index::load(&self.config.data_dir)))
+    }
+
+    /// Keep the index up to date with a document that was just written, moved or deleted. The index
+    /// is only a cache that's revalidated when opened, so failing to update it is just a warning.
+    fn index(&self, path: &Path, previous: Option<&Path>) {
+        if let Err(e) = self.with_index(|index| index.update(&self.config.data_dir, path, previous))
+        {
+            eprintln!("warning: {e:#}");
+        }
+    }
+}
+
+impl Drop for Repository<'_> {
+    fn drop(&mut self) {
+        if let Some(index) = self.index.get_mut()
+            && let Err(e) = index.save(&self.config.data_dir)
+        {
+            eprintln!("warning: {e:#}");
+        }
+    }
+}
+
+impl Store for Repository<'_> {
+    fn all(&self) -> Result<ReadAll<Application>> {
+        read_all(&self.config.data_dir)
+    }
+
+    fn records(&self) -> Result<Records> {
+        let mut records = Records {
+            records: vec![],
+            failures: self.with_index(|index| index.refresh(&self.config.data_dir))?,
+        };
+
+        self.with_index(|index| {
+            for (path, entry) in index.entries() {
+                match &entry.record {
+                    Ok(record) => records.records.push((path.to_owned(), record.to_owned())),
+                    Err(message) => records
+                        .failures
+                        .push((path.to_owned(), anyhow!(message.to_owned()))),
+                }
+            }
+        });
+
+        Ok(records)
+    }
+
+    fn modification_times(&self) -> Result<Vec<(PathBuf, SystemTime)>> {
+        self.with_index(|index| {
+            // Documents that can't be read are left out, which is a change in itself
+            index.refresh(&self.config.data_dir)?;
+
+            Ok(index
+                .entries()
+                .map(|(path, entry)| (path.to_owned(), entry.modified))
+                .collect())
+        })
+    }
+
+    fn find(&self, id_or_path: &str) -> Result<Document<Application>> {
+        let path = resolve_document(&self.config.data_dir, id_or_path)?;
+        document::read(&path)
+    }
+
+    fn create(&self, document: &Document<Application>) -> Result<PathBuf> {
+        let path = document
+            .write_new(&document.record.directory(self.config, false)?)
+            .context("failed to write application")?;
+
+        self.index(&path, None);
+
+        Ok(path)
+    }
+
+    /// Applications are moved to where the configured layout says they belong, and taken out of
+    /// the archive if they became active again. Without a layout they're otherwise left where
+    /// they are.
+    fn update(&self, document: &mut Document<Application>) -> Result<Option<PathBuf>> {
+        let path = document.write(self.config.keep_backups)?;
+
+        let archived = self.is_archived(&path);
+        let unarchived = archived && document.record.is_active();
+
+        if self.config.layout.is_none() && !unarchived {
+            self.index(&path, None);
+            return Ok(None);
+        }
+
+        let dir = document
+            .record
+            .directory(self.config, archived && !unarchived)?;
+        let moved = document.move_to(&dir)?;
+
+        self.index(moved.as_ref().unwrap_or(&path), Some(&path));
+        if moved.is_some() {
+            remove_empty_parents(&path, &self.config.data_dir);
+        }
+
+        Ok(moved)
+    }
+
+    fn rename(&self, document: &mut Document<Application>) -> Result<Option<PathBuf>> {
+        let previous = document.path.to_owned();
+        let renamed = document.normalize()?;
+
+        if let Some(renamed) = &renamed {
+            self.index(renamed, previous.as_deref());
+        }
+
+        Ok(renamed)
+    }
+
+    fn archive(&self, document: &mut Document<Application>) -> Result<Option<PathBuf>> {
+        if document
+            .path
+            .as_ref()
+            .is_some_and(|path| self.is_archived(path))
+        {
+            return Ok(None);
+        }
+
+        let previous = document.path.to_owned();
+        let dir = document.record.directory(self.config, true)?;
+        let moved = document.move_to(&dir)?;
+
+        if let Some(moved) = &moved {
+            self.index(moved, previous.as_deref());
+
+            if let Some(previous) = &previous {
+                remove_empty_parents(previous, &self.config.data_dir);
+            }
+        }
+
+        Ok(moved)
+    }
+
+    fn is_archived(&self, path: &Path) -> bool {
+        let config = self.config;
+        let (Ok(path), Ok(archive_dir)) = (
+            path.canonicalize_utf8(),
+            config
+                .data_dir
+                .join(&config.archive_dir)
+                .canonicalize_utf8(),
+        ) else {
+            return false;
+        };
+
+        path.starts_with(archive_dir)
+    }
+
+    fn delete(&self, document: Document<Application>) -> Result<()> {
+        let path = document
+            .path
+            .context("failed to delete document, it has no path")?;
+
+        fs::remove_file(&path).context(format!("failed to delete document at {path}"))?;
+        self.index(&path, None);
+        remove_empty_parents(&path, &self.config.data_dir);
+
+        Ok(())
+    }
+}
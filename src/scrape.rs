@@ -1,45 +1,109 @@
-use crate::fetch::{Fetch, Source};
-use crate::parse::Parser;
+use crate::data::write_new_atomic;
+use crate::extract::main_content;
+use crate::fetch::{Fetch, FetchOptions, Source};
+use crate::parse::{Parser, Role};
 use anyhow::{Context, Error, Result};
 use camino::Utf8Path as Path;
 use camino::Utf8PathBuf as PathBuf;
-use std::{
-    fs::{DirBuilder, File},
-    io::Write,
-};
+use chrono::{DateTime, Utc};
+use flate2::{Compression, write::GzEncoder};
+use std::{fs::DirBuilder, io::Write};
 use url::Url;
 
-use crate::job::Job;
+use crate::job::{Job, SalaryRange};
+use crate::listing::{self, SnapshotHeader};
 
 pub struct ScrapedContent {
     pub job: Job,
     pub content: String,
+    pub parser: Parser,
+    pub fetched_at: DateTime<Utc>,
 }
 
 impl ScrapedContent {
-    pub fn from_url(url: &Url) -> Result<Option<Self>> {
-        let listing = if let Some(parser) = Parser::infer(url) {
-            let content = Source::try_from(url)?.fetch()?;
-            let role = parser.parse_role(&content)?;
-
-            role.map(|role| ScrapedContent {
-                job: Job {
-                    listing_url: Some(url.to_owned()),
-                    company: role.company,
-                    title: role.title,
-                    team: role.team,
-                    salary_range: role.salary_range,
-                },
-                content,
-            })
-        } else {
-            None
+    /// Scrape the listing at the given URL, using the given parser or else inferring one from the URL
+    pub fn from_url(
+        url: &Url,
+        parser: Option<Parser>,
+        options: &FetchOptions,
+    ) -> Result<Option<Self>> {
+        let Some(parser) = parser.or_else(|| Parser::infer(url)) else {
+            return Ok(None);
+        };
+
+        let content = Source::new(url, options)?.fetch()?;
+
+        Self::from_content(url, content, Some(parser), options)
+    }
+
+    /// Scrape the listing from content that was already retrieved from the given URL, e.g. HTML
+    /// saved from a browser for boards that can't be fetched directly
+    pub fn from_content(
+        url: &Url,
+        content: String,
+        parser: Option<Parser>,
+        options: &FetchOptions,
+    ) -> Result<Option<Self>> {
+        let Some(parser) = parser.or_else(|| Parser::infer(url)) else {
+            return Ok(None);
+        };
+
+        let Some(role) = parser.parse_role(&content)? else {
+            return Ok(None);
+        };
+
+        let fetched_at = Utc::now();
+
+        let mut listing = ScrapedContent {
+            job: Job {
+                listing_url: Some(url.to_owned()),
+                source_url: role.source_url.to_owned(),
+                company: role.company,
+                title: role.title,
+                team: role.team,
+                salary_range: role.salary_range,
+                extra: toml::Table::new(),
+            },
+            content,
+            parser,
+            fetched_at,
+        };
+
+        if let Some(source_url) = &role.source_url {
+            match Self::scrape(source_url, options) {
+                Ok(Some((source_role, source_content, source_parser))) => {
+                    listing.job.company = source_role.company;
+                    listing.job.title = source_role.title;
+                    listing.job.team = source_role.team.or(listing.job.team);
+                    listing.job.salary_range =
+                        source_role.salary_range.or(listing.job.salary_range);
+                    listing.content = source_content;
+                    listing.parser = source_parser;
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("failed to scrape source listing at {source_url}: {e:#}"),
+            }
+        }
+
+        Ok(Some(listing))
+    }
+
+    fn scrape(url: &Url, options: &FetchOptions) -> Result<Option<(Role, String, Parser)>> {
+        let Some(parser) = Parser::infer(url) else {
+            return Ok(None);
         };
 
-        Ok(listing)
+        let content = Source::new(url, options)?.fetch()?;
+        let role = parser.parse_role(&content)?;
+
+        Ok(role.map(|role| (role, content, parser)))
     }
 
-    pub fn snapshot(&self, content_dir: &Path, filename: &str) -> Result<PathBuf> {
+    /// Snapshot the listing as a new version in Markdown, alongside its raw HTML so that it can be
+    /// reparsed later. No version is added if neither the description nor the salary changed since
+    /// the latest one, the salary being parsed separately as boards often give it outside of the
+    /// description.
+    pub fn snapshot(&self, content_dir: &Path, compress_html: bool) -> Result<PathBuf> {
         if content_dir.is_file() {
             return Err(Error::msg(format!(
                 "content directory {} is a file, not a directory",
@@ -55,29 +119,84 @@ impl ScrapedContent {
                 content_dir
             ))?;
 
-        let filepath = content_dir.join(filename);
-
-        if filepath.try_exists().context(format!(
-            "failed to determine if snapshotted content already exists at {}",
-            filepath
-        ))? {
-            return Ok(filepath);
-        };
-
-        let mut f = File::create_new(&filepath).context(format!(
-            "failed to create file {} for scraped content",
-            filepath
-        ))?;
+        let id = self
+            .job
+            .id()
+            .context("cannot snapshot a listing without a listing URL")?;
 
         let markdown_content = htmd::HtmlToMarkdown::builder()
-            .skip_tags(vec!["style"])
+            .skip_tags(vec![
+                "style", "script", "noscript", "nav", "header", "footer", "form",
+            ])
             .build()
-            .convert(&self.content)
+            .convert(&self.description())
             .context("failed to convert scraped HTML to markdown")?;
 
-        f.write(markdown_content.as_bytes())
+        let body = format!("{markdown_content}\n");
+
+        // A latest version that can't be read, e.g. after being edited by hand, counts as changed
+        if let Some(latest) = listing::snapshots(content_dir, &id)?.pop()
+            && latest
+                .read()
+                .is_ok_and(|(_, latest_body)| latest_body == body)
+            && latest.salary_range().ok().flatten() == self.salary_range()
+        {
+            return Ok(latest.path);
+        }
+
+        let filepath = content_dir.join(self.job.snapshot_filename(&self.fetched_at)?);
+
+        let header = toml::to_string(&SnapshotHeader {
+            listing_url: self.job.listing_url.to_owned(),
+            source_url: self.job.source_url.to_owned(),
+            fetched_at: self.fetched_at,
+            parser: self.parser.to_owned(),
+        })
+        .context("failed to serialize snapshot header")?;
+
+        write_new_atomic(&filepath, format!("---\n{header}---\n{body}").as_bytes())
             .context("failed to write scraped markdown content")?;
 
+        self.snapshot_html(&filepath, compress_html)?;
+
+        Ok(filepath)
+    }
+
+    fn salary_range(&self) -> Option<SalaryRange> {
+        self.parser
+            .parse_role(&self.content)
+            .ok()
+            .flatten()
+            .and_then(|role| role.salary_range)
+    }
+
+    /// The HTML of just the job description, falling back to the page's main content and then the
+    /// whole page
+    fn description(&self) -> String {
+        self.parser
+            .parse_description(&self.content)
+            .unwrap_or(None)
+            .or_else(|| main_content(&self.content))
+            .unwrap_or(self.content.to_owned())
+    }
+
+    fn snapshot_html(&self, markdown_path: &Path, compress: bool) -> Result<PathBuf> {
+        let filepath = markdown_path.with_extension(listing::html_extension(compress));
+
+        if compress {
+            let mut encoder = GzEncoder::new(vec![], Compression::default());
+            let compressed = encoder
+                .write_all(self.content.as_bytes())
+                .and_then(|_| encoder.finish())
+                .context("failed to compress scraped HTML")?;
+
+            write_new_atomic(&filepath, &compressed)
+                .context("failed to write compressed scraped HTML")?;
+        } else {
+            write_new_atomic(&filepath, self.content.as_bytes())
+                .context("failed to write scraped HTML")?;
+        }
+
         Ok(filepath)
     }
 }
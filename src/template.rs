@@ -0,0 +1,201 @@
+use std::{fs, io::ErrorKind};
+
+use anyhow::{Context, Result};
+use camino::Utf8Path as Path;
+use camino::Utf8PathBuf as PathBuf;
+
+use crate::{
+    PlyConfig,
+    application::{Application, Stage},
+    data::normalize_filename_attr,
+};
+
+const APPLICATION_TEMPLATE: &str = "application.md";
+const CYCLES_DIR: &str = "cycles";
+const STAGES_DIR: &str = "stages";
+const TEMPLATE_EXTENSION: &str = "md";
+
+/// Render a template, replacing each `{name}` placeholder with its value. Anything else in braces
+/// is left as it is, since braces are common enough in Markdown. Values are substituted in a single
+/// pass, so placeholders within values are left as they are too.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let placeholder = rest.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &rest[1..end])
+                .map(|(_, value)| (end, value))
+        });
+
+        match placeholder {
+            Some((end, value)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
+/// The template at the given path within the templates directory, or nothing if there isn't one.
+fn read(config: &PlyConfig, path: &Path) -> Result<Option<String>> {
+    let path = config.templates_dir.join(path);
+
+    match fs::read_to_string(&path) {
+        Ok(template) => Ok(Some(template)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).context(format!("failed to read template {path}")),
+    }
+}
+
+/// The template for new applications in a cycle, named like the layout names it so that "Fall
+/// 2026" is `cycles/fall_2026/application.md` and a cycle can't point outside the templates.
+fn cycle_template_path(cycle: &str) -> PathBuf {
+    Path::new(CYCLES_DIR)
+        .join(normalize_filename_attr(cycle))
+        .join(APPLICATION_TEMPLATE)
+}
+
+/// The notes for a new application rendered from the template for its cycle, e.g.
+/// `cycles/fall_2026/application.md` for "Fall 2026", falling back to `application.md`, or nothing
+/// if there's no template for it.
+pub fn application_notes(config: &PlyConfig, application: &Application) -> Result<Option<String>> {
+    let cycle_template = match &application.cycle {
+        Some(cycle) => read(config, &cycle_template_path(cycle))?,
+        None => None,
+    };
+
+    let template = match cycle_template {
+        Some(template) => template,
+        None => match read(config, Path::new(APPLICATION_TEMPLATE))? {
+            Some(template) => template,
+            None => return Ok(None),
+        },
+    };
+
+    let job = &application.job;
+    let applied_at = application.applied_at.format("%Y-%m-%d").to_string();
+    let salary = job
+        .salary_range
+        .as_ref()
+        .map(|salary| salary.to_string())
+        .unwrap_or_default();
+    let listing_url = job
+        .listing_url
+        .as_ref()
+        .map(|url| url.to_string())
+        .unwrap_or_default();
+    let source_url = job
+        .source_url
+        .as_ref()
+        .map(|url| url.to_string())
+        .unwrap_or_default();
+    let listing_snapshot = application
+        .listing_snapshot
+        .as_ref()
+        .map(|path| path.to_string())
+        .unwrap_or_default();
+
+    Ok(Some(render(
+        &template,
+        &[
+            ("company", &job.company),
+            ("title", &job.title),
+            ("team", job.team.as_deref().unwrap_or_default()),
+            ("cycle", application.cycle.as_deref().unwrap_or_default()),
+            ("applied_at", &applied_at),
+            ("salary", &salary),
+            ("listing_url", &listing_url),
+            ("source_url", &source_url),
+            ("listing_snapshot", &listing_snapshot),
+        ],
+    )))
+}
+
+/// The section for a stage of an application rendered from the template for its type, named as
+/// the CLI takes it, e.g. `stages/team-matching.md`, or nothing if there's no template for it.
+pub fn stage_section(
+    config: &PlyConfig,
+    application: &Application,
+    stage: &Stage,
+) -> Result<Option<String>> {
+    let path = Path::new(STAGES_DIR)
+        .join(stage.stage_type.to_string())
+        .with_extension(TEMPLATE_EXTENSION);
+
+    let Some(template) = read(config, &path)? else {
+        return Ok(None);
+    };
+
+    let job = &application.job;
+    let date = stage.start_time.format("%Y-%m-%d").to_string();
+    let deadline = stage
+        .deadline
+        .map(|deadline| deadline.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    let name = stage
+        .name
+        .to_owned()
+        .unwrap_or_else(|| stage.stage_type.title());
+
+    Ok(Some(render(
+        &template,
+        &[
+            ("company", &job.company),
+            ("title", &job.title),
+            ("team", job.team.as_deref().unwrap_or_default()),
+            ("stage", &name),
+            ("date", &date),
+            ("deadline", &deadline),
+        ],
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_known_placeholders() {
+        let rendered = render(
+            "# {title} at {company}\n\n{unknown} and {braces",
+            &[("title", "Engineer"), ("company", "Acme")],
+        );
+
+        assert_eq!(rendered, "# Engineer at Acme\n\n{unknown} and {braces");
+    }
+
+    #[test]
+    fn render_leaves_placeholders_within_values() {
+        let rendered = render(
+            "{company}: {title}",
+            &[("company", "Acme {title}"), ("title", "Engineer")],
+        );
+
+        assert_eq!(rendered, "Acme {title}: Engineer");
+    }
+
+    #[test]
+    fn cycle_templates_stay_within_cycles() {
+        assert_eq!(
+            cycle_template_path("Fall 2026"),
+            "cycles/fall_2026/application.md"
+        );
+        assert_eq!(
+            cycle_template_path("../../secrets"),
+            "cycles/__secrets/application.md"
+        );
+    }
+}